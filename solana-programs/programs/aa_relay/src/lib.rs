@@ -1,7 +1,12 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::{invoke, invoke_signed, set_return_data};
+use anchor_lang::solana_program::sysvar::instructions as instructions_sysvar;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 use anchor_lang::solana_program::system_program;
+use anchor_lang::Discriminator;
 
 // 유저 계정 프로그램 IDL
 pub mod user_account {
@@ -30,726 +35,7081 @@ pub mod user_account {
 
 declare_id!("Dv9qDFdwsHpzcNZ7KxJgChV7yzAnMjTFDYp5JGCkzKvL");
 
+// 함수 ID 상수 정의 (사용자 계정 프로그램 기준)
+pub const FUNCTION_TRANSFER: u8 = 0;
+pub const FUNCTION_REGISTER_TOKEN: u8 = 1;
+pub const FUNCTION_CREATE_SWAP: u8 = 2;
+// transfer_spl_token 함수 ID (보안 정책 및 세션 권한 범위 검증용, 사용자 계정 함수 ID와 겹치지 않음)
+pub const FUNCTION_TRANSFER_SPL_TOKEN: u8 = 3;
+// transfer_sol 함수 ID (시스템 프로그램 대상, 보안 정책 및 세션 권한 범위 검증용, 다른 함수 ID와 겹치지 않음)
+pub const FUNCTION_TRANSFER_SOL: u8 = 4;
+
+// 세션 권한 범위 제한
+pub const MAX_SCOPE_PROGRAMS: usize = 5;
+pub const MAX_SCOPE_FUNCTIONS: usize = 16;
+
+// 세션별 사용 내역을 기록하는 최대 토큰(Mint) 수
+pub const MAX_SESSION_MINTS: usize = 8;
+
+// 수수료 분배 대상 최대 수 (프로토콜, 연동 dApp, 추천인 등)
+pub const MAX_FEE_RECIPIENTS: usize = 5;
+
+// 수수료 정책별 최대 거래량 구간 수
+pub const MAX_FEE_TIERS: usize = 8;
+
+// 수수료 정책 계정 크기
+pub const FEE_POLICY_SPACE: usize = 8 + 32 + 2 + 2 + 8 + 32 +
+    8 + // max_fee_amount: u64
+    1 + // fee_mode: FeeMode
+    4 + MAX_FEE_RECIPIENTS * (32 + 2) + // fee_recipients: Vec<FeeRecipient>
+    1 + // remainder_index: u8
+    2 * (4 + MAX_FEE_TIERS * (8 + 2)); // sol_fee_tiers, token_fee_tiers: Vec<FeeTier>
+pub const TOKEN_FEE_POLICY_SPACE: usize = 8 + 32 + 2 +
+    4 + MAX_FEE_TIERS * (8 + 2); // fee_tiers: Vec<FeeTier>
+
+// 이전 버전 수수료 정책 계정 크기 (분배 대상, 구간 필드 추가 전, 마이그레이션 대상 식별용)
+pub const LEGACY_FEE_POLICY_SPACE: usize = 8 + 32 + 2 + 2 + 8 + 32;
+pub const LEGACY_TOKEN_FEE_POLICY_SPACE: usize = 8 + 32 + 2;
+
+// 수수료 구간 산정용 거래량 집계 기간 (일, VolumeTracker.daily_volumes 길이와 동일)
+pub const VOLUME_WINDOW_DAYS: usize = 30;
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+// 사용자별 최대 동시 세션 수
+pub const MAX_SESSIONS_PER_USER: usize = 10;
+
+// 사용자별 최대 가디언 수
+pub const MAX_GUARDIANS: usize = 10;
+
+// 긴급 동결 해제 요청 후 해제 가능까지의 대기 시간 (초)
+pub const UNFREEZE_DELAY: i64 = 86_400;
+
+// 서명된 인텐트 메시지 도메인 구분자
+pub const INTENT_DOMAIN: &[u8] = b"uxaa:relay_intent:v2";
+
+// 보안 정책 한도 윈도우 길이 (초)
+pub const WINDOW_HOURLY: i64 = 3_600;
+pub const WINDOW_DAILY: i64 = 86_400;
+pub const WINDOW_WEEKLY: i64 = 604_800;
+
+// 사용자별 수신자 정책 최대 주소 수
+pub const MAX_RECIPIENTS: usize = 20;
+
+// 보안 정책당 최대 토큰(Mint)별 한도 수
+pub const MAX_MINT_LIMITS: usize = 8;
+
+// 보안 정책 허용 목록 최대 항목 수 (계정은 항목 수에 맞게 재할당)
+pub const MAX_ALLOWED_FUNCTIONS: usize = 64;
+pub const MAX_ALLOWED_CALLS: usize = 64;
+
+// 보안 정책 완화 변경 대기 시간 (초)
+pub const DEFAULT_LOOSEN_DELAY: i64 = 86_400;
+pub const MAX_LOOSEN_DELAY: i64 = 2_592_000;
+
+// 롤링 윈도우 사용량 고정소수점 배율 (소수 단위 회복량 보존용)
+pub const USAGE_SCALE: u128 = 1_000_000;
+
 #[program]
 pub mod aa_relay {
     use super::*;
     
-    /// 수수료 정책 설정 함수
+    /// 릴레이 설정 초기화 함수 (프로그램 업그레이드 권한자만 가능)
+    /// 
+    /// * `admin` - 프로토콜 관리자 (긴급 철회 등 관리 작업 수행)
+    pub fn initialize_relay_config(
+        ctx: Context<InitializeRelayConfig>,
+        admin: Pubkey,
+    ) -> Result<()> {
+        let relay_config = &mut ctx.accounts.relay_config;
+        relay_config.admin = admin;
+        relay_config.pending_admin = None;
+        relay_config.paused = false;
+        
+        msg!("릴레이 설정이 초기화되었습니다. 관리자: {}", admin);
+        Ok(())
+    }
+    
+    /// 관리자 이전 제안 함수 (현재 관리자만 가능)
+    /// 
+    /// * `new_admin` - 새 관리자 (accept_admin으로 수락해야 이전 완료)
+    pub fn propose_admin(
+        ctx: Context<RelayAdminOperation>,
+        new_admin: Pubkey,
+    ) -> Result<()> {
+        let relay_config = &mut ctx.accounts.relay_config;
+        relay_config.pending_admin = Some(new_admin);
+        
+        msg!("관리자 이전이 제안되었습니다. 현재: {}, 제안: {}", relay_config.admin, new_admin);
+        Ok(())
+    }
+    
+    /// 관리자 이전 수락 함수 (제안된 새 관리자만 가능)
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let relay_config = &mut ctx.accounts.relay_config;
+        let new_admin = ctx.accounts.new_admin.key();
+        
+        require!(
+            relay_config.pending_admin == Some(new_admin),
+            ErrorCode::Unauthorized
+        );
+        
+        let previous_admin = relay_config.admin;
+        relay_config.admin = new_admin;
+        relay_config.pending_admin = None;
+        
+        msg!("관리자가 변경되었습니다. 이전: {}, 새로운: {}", previous_admin, new_admin);
+        Ok(())
+    }
+    
+    /// 릴레이 전역 일시중지/재개 함수 (관리자만 가능)
+    /// 
+    /// * `paused` - 일시중지 여부
+    pub fn set_relay_paused(
+        ctx: Context<RelayAdminOperation>,
+        paused: bool,
+    ) -> Result<()> {
+        ctx.accounts.relay_config.paused = paused;
+        
+        if paused {
+            msg!("릴레이가 일시중지되었습니다");
+        } else {
+            msg!("릴레이가 재개되었습니다");
+        }
+        Ok(())
+    }
+    
+    /// 수수료 정책 설정 함수 (릴레이 관리자만 가능)
     /// 
     /// * `fee_collector` - 수수료를 수령하는 계정
     /// * `sol_fee_bps` - SOL 전송에 대한 수수료율 (basis points, 10000 = 100%)
     /// * `token_fee_bps` - 토큰 전송에 대한 수수료율 (basis points, 10000 = 100%)
     /// * `min_fee_amount` - 최소 수수료 금액 (lamports)
+    /// * `sol_fee_tiers` - SOL 전송의 30일 거래량 구간별 수수료율 (비어 있으면 `sol_fee_bps` 고정)
+    /// * `token_fee_tiers` - 토큰 전송의 30일 거래량 구간별 수수료율 (비어 있으면 `token_fee_bps` 고정)
     pub fn set_fee_policy(
         ctx: Context<SetFeePolicy>,
         sol_fee_bps: u16,
         token_fee_bps: u16,
         min_fee_amount: u64,
+        sol_fee_tiers: Vec<FeeTier>,
+        token_fee_tiers: Vec<FeeTier>,
     ) -> Result<()> {
         let fee_policy = &mut ctx.accounts.fee_policy;
         
         // 수수료율 제한 (최대 20%)
         require!(sol_fee_bps <= 2000, ErrorCode::FeeTooHigh);
         require!(token_fee_bps <= 2000, ErrorCode::FeeTooHigh);
+        // 최대 수수료가 설정되어 있으면 최소 수수료 이상이어야 함
+        require!(
+            fee_policy.max_fee_amount == 0 || fee_policy.max_fee_amount >= min_fee_amount,
+            ErrorCode::InvalidFeeCap
+        );
+        validate_fee_tiers(&sol_fee_tiers)?;
+        validate_fee_tiers(&token_fee_tiers)?;
         
         fee_policy.fee_collector = ctx.accounts.fee_collector.key();
         fee_policy.sol_fee_bps = sol_fee_bps;
         fee_policy.token_fee_bps = token_fee_bps;
         fee_policy.min_fee_amount = min_fee_amount;
         fee_policy.authority = ctx.accounts.authority.key();
+        fee_policy.sol_fee_tiers = sol_fee_tiers;
+        fee_policy.token_fee_tiers = token_fee_tiers;
+        
+        msg!("수수료 정책이 설정되었습니다. SOL: {}bps ({}개 구간), 토큰: {}bps ({}개 구간), 최소: {} lamports",
+            sol_fee_bps, fee_policy.sol_fee_tiers.len(),
+            token_fee_bps, fee_policy.token_fee_tiers.len(), min_fee_amount);
+        Ok(())
+    }
+    
+    /// 최대 수수료 및 수수료 부담 방식 설정 함수 (릴레이 관리자만 가능)
+    /// 
+    /// * `max_fee_amount` - 최대 수수료 금액 (0이면 제한 없음)
+    /// * `fee_mode` - 수수료 부담 방식 (전송 금액에서 차감 또는 송신자 추가 부담)
+    pub fn set_fee_mode(
+        ctx: Context<UpdateFeePolicy>,
+        max_fee_amount: u64,
+        fee_mode: FeeMode,
+    ) -> Result<()> {
+        let fee_policy = &mut ctx.accounts.fee_policy;
+        
+        // 최대 수수료는 최소 수수료 이상이어야 함
+        require!(
+            max_fee_amount == 0 || max_fee_amount >= fee_policy.min_fee_amount,
+            ErrorCode::InvalidFeeCap
+        );
+        
+        fee_policy.max_fee_amount = max_fee_amount;
+        fee_policy.fee_mode = fee_mode;
+        
+        msg!("수수료 부담 방식이 설정되었습니다. 최대 수수료: {}, 송신자 부담: {}",
+            max_fee_amount, fee_mode == FeeMode::SenderPays);
+        Ok(())
+    }
+    
+    /// 수수료 분배 대상 설정 함수 (릴레이 관리자만 가능)
+    /// 
+    /// 수수료는 가중치 비율로 분배되며, 나누고 남은 나머지는 지정된 대상이 받음.
+    /// 목록이 비어 있으면 전체 수수료를 수수료 수금자(fee_collector)에게 전송
+    /// 
+    /// * `fee_recipients` - 분배 대상 지갑과 가중치 목록 (최대 MAX_FEE_RECIPIENTS개)
+    /// * `remainder_index` - 나머지를 받을 대상의 목록 내 위치
+    pub fn set_fee_recipients(
+        ctx: Context<UpdateFeePolicy>,
+        fee_recipients: Vec<FeeRecipient>,
+        remainder_index: u8,
+    ) -> Result<()> {
+        require!(
+            fee_recipients.len() <= MAX_FEE_RECIPIENTS,
+            ErrorCode::InvalidFeeRecipients
+        );
+        require!(
+            fee_recipients.is_empty() || (remainder_index as usize) < fee_recipients.len(),
+            ErrorCode::InvalidFeeRecipients
+        );
+        require!(
+            fee_recipients.iter().all(|r| r.weight > 0),
+            ErrorCode::InvalidFeeRecipients
+        );
+        for (i, fee_recipient) in fee_recipients.iter().enumerate() {
+            require!(
+                !fee_recipients[..i].iter().any(|r| r.recipient == fee_recipient.recipient),
+                ErrorCode::InvalidFeeRecipients
+            );
+        }
+        
+        let fee_policy = &mut ctx.accounts.fee_policy;
+        fee_policy.fee_recipients = fee_recipients;
+        fee_policy.remainder_index = remainder_index;
+        
+        msg!("수수료 분배 대상이 설정되었습니다. 대상 수: {}, 나머지 수령 위치: {}",
+            fee_policy.fee_recipients.len(), remainder_index);
+        Ok(())
+    }
+    
+    /// 이전 버전 수수료 정책 계정 마이그레이션 함수 (릴레이 관리자만 가능)
+    /// 
+    /// 분배 대상, 거래량 구간 필드가 추가되기 전에 생성된 계정은 현재 구조로 역직렬화되지 않으므로
+    /// 계정 크기를 재할당하고 기존 값을 유지한 채 새 필드를 기본값으로 채움
+    /// (최대 수수료 없음, 전송 금액에서 차감, 분배 대상 및 구간 없음)
+    pub fn migrate_fee_policy(ctx: Context<MigrateFeePolicy>) -> Result<()> {
+        let account_info = ctx.accounts.fee_policy.to_account_info();
+        let fee_policy = migrated_fee_policy(&account_info.try_borrow_data()?)?;
+        migrate_legacy_account(
+            &account_info,
+            &fee_policy,
+            FEE_POLICY_SPACE,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        
+        msg!("수수료 정책 계정이 마이그레이션되었습니다. {} -> {} 바이트",
+            LEGACY_FEE_POLICY_SPACE, FEE_POLICY_SPACE);
+        Ok(())
+    }
+    
+    /// 페이마스터 생성 함수 (후원 dApp)
+    /// 
+    /// 페이마스터는 후원자가 예치한 SOL로 릴레이 호출마다 수수료 지불자에게 고정 금액을 보전함
+    /// 
+    /// * `reimbursement_per_call` - 릴레이 호출당 보전 금액 (lamports)
+    /// * `daily_budget` - 전체 사용자 대상 일일 후원 한도 (lamports)
+    /// * `user_daily_budget` - 사용자별 일일 후원 한도 (lamports)
+    pub fn create_paymaster(
+        ctx: Context<CreatePaymaster>,
+        reimbursement_per_call: u64,
+        daily_budget: u64,
+        user_daily_budget: u64,
+    ) -> Result<()> {
+        validate_paymaster_budget(reimbursement_per_call, daily_budget, user_daily_budget)?;
+        
+        let paymaster = &mut ctx.accounts.paymaster;
+        paymaster.sponsor = ctx.accounts.sponsor.key();
+        paymaster.reimbursement_per_call = reimbursement_per_call;
+        paymaster.daily_budget = daily_budget;
+        paymaster.user_daily_budget = user_daily_budget;
+        paymaster.current_day = 0;
+        paymaster.spent_today = 0;
+        paymaster.total_reimbursed = 0;
+        
+        msg!("페이마스터가 생성되었습니다. 후원자: {}, 호출당 보전액: {}, 일일 한도: {}, 사용자별 일일 한도: {}",
+            paymaster.sponsor, reimbursement_per_call, daily_budget, user_daily_budget);
+        Ok(())
+    }
+    
+    /// 페이마스터 후원 한도 변경 함수 (후원자만 가능)
+    pub fn set_paymaster_budget(
+        ctx: Context<UpdatePaymaster>,
+        reimbursement_per_call: u64,
+        daily_budget: u64,
+        user_daily_budget: u64,
+    ) -> Result<()> {
+        validate_paymaster_budget(reimbursement_per_call, daily_budget, user_daily_budget)?;
+        
+        let paymaster = &mut ctx.accounts.paymaster;
+        paymaster.reimbursement_per_call = reimbursement_per_call;
+        paymaster.daily_budget = daily_budget;
+        paymaster.user_daily_budget = user_daily_budget;
+        
+        msg!("페이마스터 한도가 변경되었습니다. 호출당 보전액: {}, 일일 한도: {}, 사용자별 일일 한도: {}",
+            reimbursement_per_call, daily_budget, user_daily_budget);
+        Ok(())
+    }
+    
+    /// 페이마스터 예치 함수 (누구나 가능)
+    /// 
+    /// * `amount` - 예치 금액 (lamports)
+    pub fn fund_paymaster(ctx: Context<FundPaymaster>, amount: u64) -> Result<()> {
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.funder.to_account_info(),
+                to: ctx.accounts.paymaster.to_account_info(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_ctx, amount)?;
+        
+        msg!("페이마스터에 예치되었습니다. 예치자: {}, 금액: {} lamports",
+            ctx.accounts.funder.key(), amount);
+        Ok(())
+    }
+    
+    /// 페이마스터 출금 함수 (후원자만 가능, 렌트 면제 최소 잔액은 유지)
+    /// 
+    /// * `amount` - 출금 금액 (lamports)
+    pub fn withdraw_paymaster(ctx: Context<UpdatePaymaster>, amount: u64) -> Result<()> {
+        let paymaster_info = ctx.accounts.paymaster.to_account_info();
+        let sponsor_info = ctx.accounts.sponsor.to_account_info();
+        
+        let min_balance = Rent::get()?.minimum_balance(paymaster_info.data_len());
+        require!(
+            paymaster_info.lamports().saturating_sub(min_balance) >= amount,
+            ErrorCode::PaymasterInsufficientFunds
+        );
         
-        msg!("수수료 정책이 설정되었습니다. SOL: {}bps, 토큰: {}bps, 최소: {} lamports",
-            sol_fee_bps, token_fee_bps, min_fee_amount);
+        **paymaster_info.try_borrow_mut_lamports()? -= amount;
+        **sponsor_info.try_borrow_mut_lamports()? += amount;
+        
+        msg!("페이마스터에서 출금되었습니다. 금액: {} lamports", amount);
+        Ok(())
+    }
+    
+    /// 페이마스터 후원 대상 사용자 등록 함수 (후원자만 가능)
+    /// 
+    /// 등록된 사용자의 호출만 후원하므로, 릴레이어가 임의의 호출에 페이마스터를 붙여 금고를 소진할 수 없음
+    /// 
+    /// * `user_id` - 후원할 사용자 ID
+    pub fn add_paymaster_user(ctx: Context<AddPaymasterUser>, user_id: String) -> Result<()> {
+        let paymaster_usage = &mut ctx.accounts.paymaster_usage;
+        paymaster_usage.paymaster = ctx.accounts.paymaster.key();
+        paymaster_usage.user_id = user_id;
+        paymaster_usage.current_day = 0;
+        paymaster_usage.spent_today = 0;
+        
+        msg!("페이마스터 후원 대상이 등록되었습니다. 사용자 ID: {}", paymaster_usage.user_id);
+        Ok(())
+    }
+    
+    /// 페이마스터 후원 대상 사용자 해제 함수 (후원자만 가능, 등록 계정의 렌트는 후원자에게 반환)
+    /// 
+    /// * `user_id` - 후원을 중단할 사용자 ID
+    pub fn remove_paymaster_user(ctx: Context<RemovePaymasterUser>, user_id: String) -> Result<()> {
+        msg!("페이마스터 후원 대상이 해제되었습니다. 페이마스터: {}, 사용자 ID: {}",
+            ctx.accounts.paymaster.key(), user_id);
         Ok(())
     }
 
-    /// 토큰별 수수료 정책 설정 함수
+    /// 토큰별 수수료 정책 설정 함수 (릴레이 관리자만 가능)
     /// 
     /// * `token_mint` - 토큰 Mint 주소
     /// * `fee_bps` - 해당 토큰에 대한 수수료율 (basis points, 10000 = 100%)
+    /// * `fee_tiers` - 30일 거래량 구간별 수수료율 (비어 있으면 `fee_bps` 고정)
     pub fn set_token_fee_policy(
         ctx: Context<SetTokenFeePolicy>,
         fee_bps: u16,
+        fee_tiers: Vec<FeeTier>,
     ) -> Result<()> {
         let token_fee_policy = &mut ctx.accounts.token_fee_policy;
         
         // 수수료율 제한 (최대 20%)
         require!(fee_bps <= 2000, ErrorCode::FeeTooHigh);
+        validate_fee_tiers(&fee_tiers)?;
         
         token_fee_policy.token_mint = ctx.accounts.token_mint.key();
         token_fee_policy.fee_bps = fee_bps;
+        token_fee_policy.fee_tiers = fee_tiers;
+        
+        msg!("토큰 수수료 정책이 설정되었습니다. 토큰: {}, 수수료율: {}bps, 구간 수: {}",
+            ctx.accounts.token_mint.key(), fee_bps, token_fee_policy.fee_tiers.len());
+        Ok(())
+    }
+    
+    /// 이전 버전 토큰별 수수료 정책 계정 마이그레이션 함수 (릴레이 관리자만 가능)
+    /// 
+    /// 거래량 구간 필드가 추가되기 전에 생성된 계정의 크기를 재할당하고 구간 없음으로 채움
+    pub fn migrate_token_fee_policy(ctx: Context<MigrateTokenFeePolicy>) -> Result<()> {
+        let account_info = ctx.accounts.token_fee_policy.to_account_info();
+        let token_fee_policy = migrated_token_fee_policy(&account_info.try_borrow_data()?)?;
+        require_keys_eq!(
+            token_fee_policy.token_mint,
+            ctx.accounts.token_mint.key(),
+            ErrorCode::NotLegacyAccount
+        );
+        migrate_legacy_account(
+            &account_info,
+            &token_fee_policy,
+            TOKEN_FEE_POLICY_SPACE,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
         
-        msg!("토큰 수수료 정책이 설정되었습니다. 토큰: {}, 수수료율: {}bps",
-            ctx.accounts.token_mint.key(), fee_bps);
+        msg!("토큰 수수료 정책 계정이 마이그레이션되었습니다. 토큰: {}", ctx.accounts.token_mint.key());
         Ok(())
     }
     
     /// 보안 정책 설정 함수
     /// 
-    /// * `max_tx_per_day` - 일일 최대 트랜잭션 수
+    /// 한도는 롤링 윈도우(누수 버킷)로 적용되어 윈도우 길이에 걸쳐 한도가 서서히 회복됨.
+    /// 한도를 강화하는 변경은 즉시 적용되고, 완화하는 변경은 `loosen_delay` 이후 적용됨
+    /// 
+    /// * `window_seconds` - 한도 윈도우 길이 (WINDOW_HOURLY, WINDOW_DAILY, WINDOW_WEEKLY)
+    /// * `max_tx_per_window` - 윈도우당 최대 트랜잭션 수
     /// * `max_amount_per_tx` - 트랜잭션당 최대 금액
-    /// * `max_amount_per_day` - 일일 최대 금액
+    /// * `max_amount_per_window` - 윈도우당 최대 금액
+    /// * `default_mint_allowed` - 토큰별 한도가 없는 토큰의 전송 허용 여부
     pub fn set_security_policy(
         ctx: Context<SetSecurityPolicy>,
-        max_tx_per_day: u32,
+        window_seconds: i64,
+        max_tx_per_window: u32,
         max_amount_per_tx: u64,
-        max_amount_per_day: u64,
+        max_amount_per_window: u64,
         allowed_functions: Vec<u8>,
+        default_mint_allowed: bool,
     ) -> Result<()> {
+        require!(
+            window_seconds == WINDOW_HOURLY
+                || window_seconds == WINDOW_DAILY
+                || window_seconds == WINDOW_WEEKLY,
+            ErrorCode::InvalidSecurityPolicy
+        );
+        require!(
+            allowed_functions.len() <= MAX_ALLOWED_FUNCTIONS,
+            ErrorCode::InvalidSecurityPolicy
+        );
+        
         let security_policy = &mut ctx.accounts.security_policy;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        let mut limits = security_policy.limits.clone();
+        limits.window_seconds = window_seconds;
+        limits.max_tx_per_window = max_tx_per_window;
+        limits.max_amount_per_tx = max_amount_per_tx;
+        limits.max_amount_per_window = max_amount_per_window;
+        limits.allowed_functions = allowed_functions;
+        limits.default_mint_allowed = default_mint_allowed;
+        
+        if security_policy.user_id.is_empty() {
+            // 최초 생성: 보호할 기존 정책이 없으므로 즉시 적용
+            limits.loosen_delay = DEFAULT_LOOSEN_DELAY;
+            security_policy.user_id = ctx.accounts.key_mapping.user_id.clone();
+            security_policy.limits = limits;
+            security_policy.tx_usage = 0;
+            security_policy.amount_usage = 0;
+            security_policy.last_update = current_time;
+        } else {
+            let loosening = is_loosening_limits(&security_policy.limits, &limits);
+            stage_policy_change(security_policy, PolicyChange::Limits(limits), loosening, current_time)?;
+        }
         
-        security_policy.user_id = ctx.accounts.key_mapping.user_id.clone();
-        security_policy.max_tx_per_day = max_tx_per_day;
-        security_policy.max_amount_per_tx = max_amount_per_tx;
-        security_policy.max_amount_per_day = max_amount_per_day;
-        security_policy.daily_tx_count = 0;
-        security_policy.daily_amount = 0;
-        security_policy.last_day = Clock::get()?.unix_timestamp / 86400;
-        security_policy.allowed_functions = allowed_functions;
+        msg!("보안 정책 설정 요청이 처리되었습니다. 사용자 ID: {}, 윈도우: {}초, 윈도우당 최대 트랜잭션: {}, 트랜잭션당 최대 금액: {}",
+            security_policy.user_id, window_seconds, max_tx_per_window, max_amount_per_tx);
         
-        msg!("보안 정책이 설정되었습니다. 사용자 ID: {}, 일일 최대 트랜잭션: {}, 트랜잭션당 최대 금액: {}",
-            security_policy.user_id, max_tx_per_day, max_amount_per_tx);
+        // 변경된 내용에 맞게 계정 크기 조정
+        resize_security_policy(
+            &ctx.accounts.security_policy,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            true,
+        )?;
         Ok(())
     }
     
-    /// 임시 키 및 영구 백업 키 등록 함수
+    /// 토큰(Mint)별 전송 한도 설정 함수 (기존 항목이 있으면 한도만 갱신)
     /// 
-    /// * `user_id` - 사용자 고유 식별자 (오프체인에서 관리)
-    /// * `expires_at` - 임시 키 만료 시간
-    pub fn register_temp_keys(
-        ctx: Context<RegisterTempKeys>,
-        user_id: String,
-        expires_at: i64,
+    /// 한도 상향, 기본 거부 토큰의 신규 허용, 전역 금액 한도보다 큰 신규 항목 및 첫 항목 추가는
+    /// 완화 변경으로 대기 시간 이후 적용됨 (is_mint_limit_loosened 참고)
+    /// 
+    /// * `mint` - 한도를 적용할 토큰 Mint 주소
+    /// * `max_amount_per_tx` - 트랜잭션당 최대 금액 (토큰 최소 단위)
+    /// * `max_amount_per_window` - 보안 정책 윈도우당 최대 금액 (토큰 최소 단위)
+    pub fn set_mint_limit(
+        ctx: Context<UpdateSecurityPolicy>,
+        mint: Pubkey,
+        max_amount_per_tx: u64,
+        max_amount_per_window: u64,
     ) -> Result<()> {
-        let key_mapping = &mut ctx.accounts.key_mapping;
-        
-        // 키 매핑 정보 저장
-        key_mapping.temp_key = ctx.accounts.temp_key.key();
-        key_mapping.backup_key = ctx.accounts.backup_key.key();
-        key_mapping.user_id = user_id.clone();
-        key_mapping.expires_at = expires_at;
-        key_mapping.revoked = false;
-        key_mapping.created_at = Clock::get()?.unix_timestamp;
+        let security_policy = &mut ctx.accounts.security_policy;
+        let current_time = Clock::get()?.unix_timestamp;
         
-        // 사용자 계정 PDA 계산 (추후 트랜잭션 릴레이에서 사용)
-        let (user_account_pda, _) = Pubkey::find_program_address(
-            &[b"user_account", user_id.as_bytes()],
-            &user_account::UserAccount::id(),
+        require!(
+            security_policy.mint_limits.len() < MAX_MINT_LIMITS
+                || security_policy.mint_limits.iter().any(|l| l.mint == mint),
+            ErrorCode::MintLimitCapacityReached
         );
-        key_mapping.user_account_pda = user_account_pda;
+        let loosening = is_mint_limit_loosened(
+            security_policy,
+            mint,
+            max_amount_per_tx,
+            max_amount_per_window,
+        );
+        let change = PolicyChange::SetMintLimit {
+            mint,
+            max_amount_per_tx,
+            max_amount_per_window,
+        };
+        stage_policy_change(security_policy, change, loosening, current_time)?;
         
-        msg!("임시 키와 영구 백업 키가 등록되었습니다. 임시 키: {}, 백업 키: {}, 사용자 ID: {}", 
-            ctx.accounts.temp_key.key(), ctx.accounts.backup_key.key(), user_id);
+        msg!("토큰별 한도 설정 요청이 처리되었습니다. 토큰: {}, 트랜잭션당 최대: {}, 윈도우당 최대: {}",
+            mint, max_amount_per_tx, max_amount_per_window);
+        
+        // 변경된 내용에 맞게 계정 크기 조정
+        resize_security_policy(
+            &ctx.accounts.security_policy,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            true,
+        )?;
         Ok(())
     }
     
-    /// 임시 키 철회 함수
-    pub fn revoke_temp_key(ctx: Context<RevokeTempKey>) -> Result<()> {
-        let key_mapping = &mut ctx.accounts.key_mapping;
+    /// 토큰(Mint)별 전송 한도 삭제 함수
+    /// 
+    /// 삭제 후 해당 토큰은 `default_mint_allowed` 설정을 따름 (기본 허용인 경우 완화 변경)
+    pub fn remove_mint_limit(ctx: Context<UpdateSecurityPolicy>, mint: Pubkey) -> Result<()> {
+        let security_policy = &mut ctx.accounts.security_policy;
+        let current_time = Clock::get()?.unix_timestamp;
         
-        // 이미 철회된 키인지 확인
-        require!(!key_mapping.revoked, ErrorCode::AlreadyRevoked);
+        require!(
+            security_policy.mint_limits.iter().any(|l| l.mint == mint),
+            ErrorCode::MintLimitNotFound
+        );
+        let loosening = security_policy.limits.default_mint_allowed;
+        stage_policy_change(
+            security_policy,
+            PolicyChange::RemoveMintLimit { mint },
+            loosening,
+            current_time,
+        )?;
         
-        // 권한 검증 (관리자만 철회 가능)
+        msg!("토큰별 한도 삭제 요청이 처리되었습니다. 토큰: {}", mint);
+        
+        // 변경된 내용에 맞게 계정 크기 조정
+        resize_security_policy(
+            &ctx.accounts.security_policy,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            true,
+        )?;
+        Ok(())
+    }
+    
+    /// 보안 정책 시간 제한 설정 함수
+    /// 
+    /// 시작 시각과 종료 시각이 같으면 시간대 제한 없음. 시작 시각이 더 크면 자정을 넘는 구간 (예: 22시~6시)
+    /// 
+    /// * `min_tx_interval` - 트랜잭션 간 최소 간격 (초, 0이면 제한 없음)
+    /// * `allowed_hour_start` - 허용 시간대 시작 시각 (현지 시각 0~23, 포함)
+    /// * `allowed_hour_end` - 허용 시간대 종료 시각 (현지 시각 0~23, 미포함)
+    /// * `utc_offset_minutes` - 현지 시각 계산용 UTC 오프셋 (분, -720 ~ 840)
+    pub fn set_time_restrictions(
+        ctx: Context<UpdateSecurityPolicy>,
+        min_tx_interval: i64,
+        allowed_hour_start: u8,
+        allowed_hour_end: u8,
+        utc_offset_minutes: i16,
+    ) -> Result<()> {
         require!(
-            ctx.accounts.authority.key() == ctx.accounts.payer.key(),
-            ErrorCode::Unauthorized
+            min_tx_interval >= 0
+                && allowed_hour_start < 24
+                && allowed_hour_end < 24
+                && (-720..=840).contains(&utc_offset_minutes),
+            ErrorCode::InvalidSecurityPolicy
         );
         
-        // 임시 키 철회
-        key_mapping.revoked = true;
+        let security_policy = &mut ctx.accounts.security_policy;
+        let current_time = Clock::get()?.unix_timestamp;
         
-        msg!("임시 키가 철회되었습니다: {}", key_mapping.temp_key);
+        let mut limits = security_policy.limits.clone();
+        limits.min_tx_interval = min_tx_interval;
+        limits.allowed_hour_start = allowed_hour_start;
+        limits.allowed_hour_end = allowed_hour_end;
+        limits.utc_offset_minutes = utc_offset_minutes;
+        
+        let loosening = is_loosening_limits(&security_policy.limits, &limits);
+        stage_policy_change(security_policy, PolicyChange::Limits(limits), loosening, current_time)?;
+        
+        msg!("시간 제한 설정 요청이 처리되었습니다. 최소 간격: {}초, 허용 시간대: {}시~{}시, UTC 오프셋: {}분",
+            min_tx_interval, allowed_hour_start, allowed_hour_end, utc_offset_minutes);
+        
+        // 변경된 내용에 맞게 계정 크기 조정
+        resize_security_policy(
+            &ctx.accounts.security_policy,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            true,
+        )?;
         Ok(())
     }
-
-    /// 백업 키 변경 함수 (백업 키 소유자만 변경 가능)
-    pub fn change_backup_key(ctx: Context<ChangeBackupKey>) -> Result<()> {
-        let key_mapping = &mut ctx.accounts.key_mapping;
+    
+    /// 정책 완화 대기 시간 설정 함수
+    /// 
+    /// 대기 시간 단축은 그 자체로 완화 변경이므로 기존 대기 시간 이후 적용됨
+    /// 
+    /// * `loosen_delay` - 완화 변경 적용까지의 대기 시간 (초, 최대 MAX_LOOSEN_DELAY)
+    pub fn set_policy_timelock(ctx: Context<UpdateSecurityPolicy>, loosen_delay: i64) -> Result<()> {
+        require!(
+            (0..=MAX_LOOSEN_DELAY).contains(&loosen_delay),
+            ErrorCode::InvalidSecurityPolicy
+        );
         
-        // 백업 키 소유자 검증 (현재 백업 키 소유자만 변경 가능)
+        let security_policy = &mut ctx.accounts.security_policy;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        let mut limits = security_policy.limits.clone();
+        limits.loosen_delay = loosen_delay;
+        
+        let loosening = is_loosening_limits(&security_policy.limits, &limits);
+        stage_policy_change(security_policy, PolicyChange::Limits(limits), loosening, current_time)?;
+        
+        msg!("정책 완화 대기 시간 설정 요청이 처리되었습니다. 대기 시간: {}초", loosen_delay);
+        
+        // 변경된 내용에 맞게 계정 크기 조정
+        resize_security_policy(
+            &ctx.accounts.security_policy,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            true,
+        )?;
+        Ok(())
+    }
+    
+    /// 호출 허용 목록 설정 함수
+    /// 
+    /// 목록이 비어 있으면 모든 (프로그램, 함수) 조합을 허용. 목록에 없는 조합을 추가하면 완화 변경
+    /// 
+    /// * `allowed_calls` - 허용할 (프로그램 ID, 함수 ID) 목록 (최대 MAX_ALLOWED_CALLS개)
+    pub fn set_allowed_calls(
+        ctx: Context<UpdateSecurityPolicy>,
+        allowed_calls: Vec<AllowedCall>,
+    ) -> Result<()> {
         require!(
-            ctx.accounts.current_backup_key_signer.key() == key_mapping.backup_key,
-            ErrorCode::Unauthorized
+            allowed_calls.len() <= MAX_ALLOWED_CALLS,
+            ErrorCode::InvalidSecurityPolicy
         );
         
-        // 백업 키 변경
-        key_mapping.backup_key = ctx.accounts.new_backup_key.key();
+        let security_policy = &mut ctx.accounts.security_policy;
+        let current_time = Clock::get()?.unix_timestamp;
         
-        msg!("백업 키가 변경되었습니다. 이전: {}, 새로운: {}", 
-            ctx.accounts.current_backup_key_signer.key(), ctx.accounts.new_backup_key.key());
+        let mut limits = security_policy.limits.clone();
+        limits.allowed_calls = allowed_calls;
+        
+        let loosening = is_loosening_limits(&security_policy.limits, &limits);
+        stage_policy_change(security_policy, PolicyChange::Limits(limits), loosening, current_time)?;
+        
+        msg!("호출 허용 목록 설정 요청이 처리되었습니다. 사용자 ID: {}", security_policy.user_id);
+        
+        // 변경된 내용에 맞게 계정 크기 조정
+        resize_security_policy(
+            &ctx.accounts.security_policy,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            true,
+        )?;
         Ok(())
     }
     
-    /// SPL 토큰 전송 함수
-    pub fn transfer_spl_token(
-        ctx: Context<TransferSplToken>,
+    /// 보안 정책 사전 검증 함수 (읽기 전용)
+    /// 
+    /// 계정 상태를 변경하지 않고 결과(PolicyCheckResult)를 반환 데이터로 설정하므로
+    /// 클라이언트는 시뮬레이션으로 거부 여부와 남은 한도를 미리 확인할 수 있음.
+    /// 보안 정책이 아직 생성되지 않은 사용자는 실제 호출에서도 정책 검증이 없으므로 제한 없음으로 응답
+    /// 
+    /// * `user_id` - 사용자 고유 식별자
+    /// * `target_program` - 호출 대상 프로그램
+    /// * `function_id` - 호출 함수 ID
+    /// * `amount` - 이동할 금액
+    /// * `mint` - 전송할 토큰 Mint (토큰 전송이 아니면 None)
+    pub fn check_policy(
+        ctx: Context<CheckPolicy>,
+        user_id: String,
+        target_program: Pubkey,
+        function_id: u8,
         amount: u64,
+        mint: Option<Pubkey>,
     ) -> Result<()> {
-        let key_mapping = &ctx.accounts.key_mapping;
         let current_time = Clock::get()?.unix_timestamp;
-        let temp_key = ctx.accounts.temp_key.key();
         
-        // 서명자가 주 임시 키인지 백업 키인지 확인
-        let is_main_key = temp_key == key_mapping.temp_key;
-        let is_backup_key = temp_key == key_mapping.backup_key;
+        let result = match load_security_policy(&ctx.accounts.security_policy, ctx.program_id)? {
+            Some(security_policy) => {
+                // 복사본에 검증을 적용하여 실제 계정은 변경하지 않음
+                let mut simulated: SecurityPolicy = (*security_policy).clone();
+                let decision = apply_security_policy(
+                    &mut simulated,
+                    &target_program,
+                    function_id,
+                    mint,
+                    amount,
+                    current_time,
+                );
+                policy_check_result(&security_policy, decision, mint, current_time)
+            }
+            None => unrestricted_policy_check_result(),
+        };
+        set_return_data(&result.try_to_vec()?);
+        
+        msg!("보안 정책 사전 검증: 사용자 ID {}, 정책 {}, 허용 {}, 사유 코드 {}, 남은 트랜잭션 {}, 남은 금액 {}",
+            user_id, result.policy_exists, result.allowed, result.reason_code,
+            result.remaining_tx, result.remaining_amount);
+        Ok(())
+    }
+    
+    /// 대기 중인 정책 완화 변경 적용 함수 (대기 시간 경과 후 누구나 호출 가능)
+    pub fn apply_policy_change(ctx: Context<ApplyPolicyChange>) -> Result<()> {
+        let security_policy = &mut ctx.accounts.security_policy;
+        let current_time = Clock::get()?.unix_timestamp;
         
+        let pending = security_policy
+            .pending_change
+            .take()
+            .ok_or(ErrorCode::NoPendingPolicyChange)?;
         require!(
-            is_main_key || is_backup_key,
-            ErrorCode::InvalidKeySigner
+            current_time >= pending.executable_at,
+            ErrorCode::PolicyTimelockActive
         );
         
-        // 임시 키인 경우 만료 시간 확인
-        if is_main_key {
-            require!(
-                current_time < key_mapping.expires_at,
-                ErrorCode::SessionExpired
-            );
-        }
+        execute_policy_change(
+            security_policy,
+            pending.change,
+            ctx.accounts.recipient_policy.as_deref_mut(),
+            current_time,
+        )?;
         
-        // 보안 정책 검증
-        if let Some(security_policy) = &mut ctx.accounts.security_policy {
-            // 일자가 바뀌었는지 체크 (단일 비교로 효율화)
-            let current_day = current_time / 86400;
-            
-            if security_policy.last_day != current_day {
-                security_policy.daily_tx_count = 0;
-                security_policy.daily_amount = 0;
-                security_policy.last_day = current_day;
-            } else {
-                // 트랜잭션 수 제한 확인
-                if security_policy.daily_tx_count >= security_policy.max_tx_per_day {
-                    return Err(ErrorCode::DailyTxLimitExceeded.into());
-                }
-                
-                // 함수 ID 허용 확인 (간소화된 체크)
-                if !security_policy.allowed_functions.is_empty() && 
-                   !security_policy.allowed_functions.contains(&2) { // 2: transfer_spl_token 함수 ID
-                    return Err(ErrorCode::FunctionNotAllowed.into());
-                }
-            }
-            
-            // 트랜잭션 카운트 증가 (한 번만 업데이트)
-            security_policy.daily_tx_count += 1;
-        }
+        msg!("대기 중인 정책 변경이 적용되었습니다. 사용자 ID: {}", security_policy.user_id);
+        
+        // 변경된 내용에 맞게 계정 크기 확장 (환급 없음)
+        resize_security_policy(
+            &ctx.accounts.security_policy,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            false,
+        )?;
+        Ok(())
+    }
+    
+    /// 대기 중인 정책 완화 변경 취소 함수 (철회되지 않은 키 매핑의 백업 키 또는 사용자 소유자만 가능)
+    pub fn cancel_policy_change(ctx: Context<CancelPolicyChange>) -> Result<()> {
+        check_policy_cancel_authority(
+            &ctx.accounts.authority.key(),
+            &ctx.accounts.key_mapping,
+            &ctx.accounts.user_authority,
+        )?;
         
-        // 수수료 계산
-        let mut fee_amount = 0;
+        let security_policy = &mut ctx.accounts.security_policy;
+        require!(
+            security_policy.pending_change.is_some(),
+            ErrorCode::NoPendingPolicyChange
+        );
+        security_policy.pending_change = None;
         
-        if ctx.accounts.fee_policy.is_some() && ctx.accounts.token_fee_policy.is_some() {
-            let fee_policy = ctx.accounts.fee_policy.as_ref().unwrap();
-            let token_fee_policy = ctx.accounts.token_fee_policy.as_ref().unwrap();
-            
-            // 토큰별 수수료율 또는 기본 토큰 수수료율 적용
-            let fee_bps = if token_fee_policy.token_mint == ctx.accounts.mint.key() {
-                token_fee_policy.fee_bps
-            } else {
-                fee_policy.token_fee_bps
-            };
-            
-            // 수수료 계산 (basis points)
-            fee_amount = (amount as u128 * fee_bps as u128 / 10000) as u64;
-            
-            // 최소 수수료 적용
-            if fee_amount < fee_policy.min_fee_amount {
-                fee_amount = fee_policy.min_fee_amount;
+        msg!("대기 중인 정책 변경이 취소되었습니다. 사용자 ID: {}", security_policy.user_id);
+        Ok(())
+    }
+    
+    /// 수신자 정책 설정 함수 (사용자 소유자만 가능)
+    /// 
+    /// 기존 주소 목록은 전달된 목록으로 교체됨. 보안 정책이 있으면 제한을 완화하는 변경
+    /// (Open 전환, 허용 주소 추가, 차단 주소 제거 등)은 `loosen_delay` 이후 `apply_policy_change`로 적용됨
+    /// 
+    /// * `user_id` - 사용자 고유 식별자
+    /// * `mode` - 수신자 제한 방식 (Open, Allowlist, Denylist)
+    /// * `recipients` - 허용 또는 차단할 주소 목록 (지갑 또는 토큰 계정, 최대 MAX_RECIPIENTS개)
+    pub fn set_recipient_policy(
+        ctx: Context<SetRecipientPolicy>,
+        user_id: String,
+        mode: RecipientMode,
+        recipients: Vec<Pubkey>,
+    ) -> Result<()> {
+        require!(
+            recipients.len() <= MAX_RECIPIENTS,
+            ErrorCode::InvalidRecipientPolicy
+        );
+        
+        let current_time = Clock::get()?.unix_timestamp;
+        let recipient_policy = &mut ctx.accounts.recipient_policy;
+        recipient_policy.user_id = user_id;
+        
+        let loosening = is_recipient_policy_loosened(recipient_policy, mode, &recipients);
+        let change = PolicyChange::SetRecipientPolicy { mode, recipients };
+        
+        // 보안 정책이 있으면 완화 변경은 대기 시간 적용
+        let immediate = match load_security_policy(&ctx.accounts.security_policy, ctx.program_id)? {
+            Some(mut security_policy) => {
+                let immediate = queue_policy_change(&mut security_policy, change, loosening, current_time);
+                resize_security_policy(
+                    &security_policy,
+                    &ctx.accounts.owner.to_account_info(),
+                    &ctx.accounts.system_program.to_account_info(),
+                    false,
+                )?;
+                security_policy.exit(ctx.program_id)?;
+                immediate
             }
+            None => Some(change),
+        };
+        
+        if let Some(PolicyChange::SetRecipientPolicy { mode, recipients }) = immediate {
+            recipient_policy.mode = mode;
+            recipient_policy.recipients = recipients;
             
-            // 수수료가 전송 금액보다 크면 오류
-            require!(
-                fee_amount < amount,
-                ErrorCode::FeeTooHigh
-            );
-            
-            msg!("수수료 정보: 금액 = {}, 수수료 = {}, 수신자 수령액 = {}", 
-                amount, fee_amount, amount - fee_amount);
-            
-            // 수수료 전송 (수수료가 있는 경우)
-            if fee_amount > 0 {
-                // 토큰 계정에서 수수료 수령자에게 수수료 전송
-                let cpi_accounts = Transfer {
-                    from: ctx.accounts.from.to_account_info(),
-                    to: ctx.accounts.fee_collector.to_account_info(),
-                    authority: ctx.accounts.temp_key.to_account_info(),
-                };
-                
-                let cpi_program = ctx.accounts.token_program.to_account_info();
-                let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-                
-                token::transfer(cpi_ctx, fee_amount)?;
-            }
+            msg!("수신자 정책이 설정되었습니다. 사용자 ID: {}, 방식: {}, 주소: {}개",
+                recipient_policy.user_id, mode as u8, recipient_policy.recipients.len());
         }
+        Ok(())
+    }
+    
+    /// 사용자 소유자 권한 등록 함수 (사용자 계정 생성자만 가능)
+    /// 
+    /// 등록된 소유자는 세션 등록, 백업 키 변경, 보안 정책 변경을 승인함
+    /// 
+    /// * `user_id` - 사용자 고유 식별자
+    pub fn register_owner(
+        ctx: Context<RegisterOwner>,
+        user_id: String,
+    ) -> Result<()> {
+        // 사용자 계정 PDA의 생성자(creator)인지 확인
+        let user_account_info = &ctx.accounts.user_account;
+        require!(
+            user_account_info.owner == &user_account::UserAccount::id(),
+            ErrorCode::InvalidUserAccount
+        );
         
-        // 토큰 전송 (수수료를 제외한 금액)
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.from.to_account_info(),
-            to: ctx.accounts.to.to_account_info(),
-            authority: ctx.accounts.temp_key.to_account_info(),
+        let creator = {
+            let data = user_account_info.try_borrow_data()?;
+            require!(data.len() >= 40, ErrorCode::InvalidUserAccount);
+            Pubkey::new_from_array(data[8..40].try_into().unwrap())
         };
+        require!(
+            creator == ctx.accounts.creator.key(),
+            ErrorCode::Unauthorized
+        );
         
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        let user_authority = &mut ctx.accounts.user_authority;
+        user_authority.user_id = user_id;
+        user_authority.owner = ctx.accounts.owner.key();
+        user_authority.created_at = Clock::get()?.unix_timestamp;
         
-        token::transfer(cpi_ctx, amount - fee_amount)?;
+        msg!("사용자 소유자가 등록되었습니다. 사용자 ID: {}, 소유자: {}",
+            user_authority.user_id, user_authority.owner);
+        Ok(())
+    }
+    
+    /// 임시 키 및 영구 백업 키 등록 함수 (소유자 서명 필요)
+    /// 
+    /// * `user_id` - 사용자 고유 식별자 (오프체인에서 관리)
+    /// * `expires_at` - 임시 키 만료 시간
+    /// * `scope` - 세션별 권한 범위 (허용 프로그램, 허용 함수, 지출 한도, 사용 횟수)
+    pub fn register_temp_keys(
+        ctx: Context<RegisterTempKeys>,
+        user_id: String,
+        expires_at: i64,
+        scope: SessionScope,
+    ) -> Result<()> {
+        let key_mapping = &mut ctx.accounts.key_mapping;
         
-        msg!("SPL 토큰 전송이 완료되었습니다. 수신자: {}, 금액: {}", 
-            ctx.accounts.to.key(), amount - fee_amount);
+        // 권한 범위 크기 검증
+        require!(
+            scope.allowed_programs.len() <= MAX_SCOPE_PROGRAMS
+                && scope.allowed_functions.len() <= MAX_SCOPE_FUNCTIONS,
+            ErrorCode::InvalidSessionScope
+        );
+        
+        // 키 매핑 정보 저장
+        key_mapping.temp_key = ctx.accounts.temp_key.key();
+        key_mapping.backup_key = ctx.accounts.backup_key.key();
+        key_mapping.user_id = user_id.clone();
+        key_mapping.expires_at = expires_at;
+        key_mapping.revoked = false;
+        key_mapping.created_at = Clock::get()?.unix_timestamp;
+        key_mapping.scope = scope;
+        key_mapping.spent_amount = 0;
+        key_mapping.use_count = 0;
+        key_mapping.payer = ctx.accounts.payer.key();
+        key_mapping.frozen = false;
+        key_mapping.usage = SessionUsage::default();
+        
+        // 사용자 계정 PDA 계산 (추후 트랜잭션 릴레이에서 사용)
+        let (user_account_pda, _) = Pubkey::find_program_address(
+            &[b"user_account", user_id.as_bytes()],
+            &user_account::UserAccount::id(),
+        );
+        key_mapping.user_account_pda = user_account_pda;
+        
+        // 세션 레지스트리에 등록 (만료된 세션은 정리 후 추가)
+        let session_registry = &mut ctx.accounts.session_registry;
+        if session_registry.user_id.is_empty() {
+            session_registry.user_id = user_id.clone();
+        }
+        require!(!session_registry.frozen, ErrorCode::SessionFrozen);
+        prune_expired_sessions(session_registry, key_mapping.created_at);
+        require!(
+            session_registry.sessions.len() < MAX_SESSIONS_PER_USER,
+            ErrorCode::SessionLimitReached
+        );
+        session_registry.sessions.push(SessionEntry {
+            temp_key: key_mapping.temp_key,
+            expires_at,
+            created_at: key_mapping.created_at,
+        });
+        
+        msg!("임시 키와 영구 백업 키가 등록되었습니다. 임시 키: {}, 백업 키: {}, 사용자 ID: {}", 
+            ctx.accounts.temp_key.key(), ctx.accounts.backup_key.key(), user_id);
+        msg!("세션 권한 범위: 프로그램 {}개, 함수 {}개, 지출 한도: {}, 최대 사용 횟수: {}",
+            key_mapping.scope.allowed_programs.len(), key_mapping.scope.allowed_functions.len(),
+            key_mapping.scope.spend_limit, key_mapping.scope.max_uses);
         Ok(())
     }
     
-    /// 트랜잭션 릴레이 함수
-    /// 임시 키나 백업 키를 사용하여 유저 계정 프로그램 호출
-    pub fn relay_transaction(
-        ctx: Context<RelayTransaction>,
-        function_id: u8,
-        params: Vec<u8>,
+    /// 임시 키 철회 함수
+    /// 
+    /// 철회 권한: 세션 보유자(임시 키) 본인, 백업 키, 사용자 소유자, 프로토콜 관리자(긴급 철회)
+    pub fn revoke_temp_key(ctx: Context<RevokeTempKey>) -> Result<()> {
+        let key_mapping = &mut ctx.accounts.key_mapping;
+        
+        // 이미 철회된 키인지 확인
+        require!(!key_mapping.revoked, ErrorCode::AlreadyRevoked);
+        
+        // 서명자 역할에 따른 철회 사유 결정
+        let reason = revoke_reason(
+            &ctx.accounts.authority.key(),
+            key_mapping,
+            &ctx.accounts.user_authority,
+            ctx.accounts.relay_config.as_deref(),
+        )
+        .ok_or(ErrorCode::Unauthorized)?;
+        
+        // 임시 키 철회 및 세션 레지스트리에서 제거
+        key_mapping.revoked = true;
+        remove_session(&mut ctx.accounts.session_registry, &key_mapping.temp_key);
+        
+        msg!("임시 키가 철회되었습니다: {}, 철회자: {}, 사유 코드: {}",
+            key_mapping.temp_key, ctx.accounts.authority.key(), reason as u8);
+        Ok(())
+    }
+    
+    /// 사용자의 모든 세션 일괄 철회 함수 (사용자 소유자 또는 모든 세션의 백업 키 소유자만 가능)
+    /// 
+    /// remaining_accounts로 세션 레지스트리에 등록된 모든 KeyMapping 계정을 전달해야 함
+    /// 
+    /// * `user_id` - 사용자 고유 식별자
+    pub fn revoke_all_sessions<'info>(
+        ctx: Context<'_, '_, '_, 'info, RevokeAllSessions<'info>>,
+        user_id: String,
     ) -> Result<()> {
+        let session_registry = &mut ctx.accounts.session_registry;
+        let authority = ctx.accounts.authority.key();
+        
+        // 만료된 세션은 이미 비활성이므로 정리 후 검증
+        let current_time = Clock::get()?.unix_timestamp;
+        prune_expired_sessions(session_registry, current_time);
+        
+        // 레지스트리의 모든 활성 세션이 전달되었는지 확인
+        let key_mappings = load_active_key_mappings(
+            ctx.remaining_accounts,
+            session_registry,
+            current_time,
+            ctx.program_id,
+        )?;
+        
+        // 상태를 변경하기 전에 철회 권한 확인
+        let backup_keys: Vec<Pubkey> = key_mappings.iter().map(|k| k.backup_key).collect();
+        check_revoke_all_authority(&authority, &ctx.accounts.user_authority, &backup_keys)?;
+        
+        for mut key_mapping in key_mappings {
+            key_mapping.revoked = true;
+            key_mapping.exit(ctx.program_id)?;
+        }
+        
+        let revoked_count = session_registry.sessions.len();
+        session_registry.sessions.clear();
+        
+        msg!("모든 세션이 철회되었습니다. 사용자 ID: {}, 철회된 세션 수: {}", user_id, revoked_count);
+        Ok(())
+    }
+
+    /// 백업 키 변경 함수 (백업 키 소유자와 사용자 소유자의 서명 필요)
+    pub fn change_backup_key(ctx: Context<ChangeBackupKey>) -> Result<()> {
         let key_mapping = &mut ctx.accounts.key_mapping;
+        
+        // 백업 키 소유자 검증 (현재 백업 키 소유자만 변경 가능)
+        require!(
+            ctx.accounts.current_backup_key_signer.key() == key_mapping.backup_key,
+            ErrorCode::Unauthorized
+        );
+        
+        // 백업 키 변경
+        key_mapping.backup_key = ctx.accounts.new_backup_key.key();
+        
+        msg!("백업 키가 변경되었습니다. 이전: {}, 새로운: {}", 
+            ctx.accounts.current_backup_key_signer.key(), ctx.accounts.new_backup_key.key());
+        Ok(())
+    }
+    
+    /// 가디언 설정 함수 (사용자 소유자만 가능)
+    /// 
+    /// 설정을 변경하면 진행 중인 복구 요청은 취소됨
+    /// 
+    /// * `user_id` - 사용자 고유 식별자
+    /// * `guardians` - 가디언 목록 (최대 MAX_GUARDIANS개)
+    /// * `threshold` - 복구 승인에 필요한 가디언 수 (M-of-N)
+    /// * `recovery_delay` - 승인 완료 후 복구 실행까지의 대기 시간 (초)
+    pub fn set_guardians(
+        ctx: Context<SetGuardians>,
+        user_id: String,
+        guardians: Vec<Pubkey>,
+        threshold: u8,
+        recovery_delay: i64,
+    ) -> Result<()> {
+        require!(
+            !guardians.is_empty() && guardians.len() <= MAX_GUARDIANS,
+            ErrorCode::InvalidGuardianConfig
+        );
+        require!(
+            threshold >= 1 && threshold as usize <= guardians.len(),
+            ErrorCode::InvalidGuardianConfig
+        );
+        require!(recovery_delay > 0, ErrorCode::InvalidGuardianConfig);
+        
+        // 중복 가디언 확인
+        for (i, guardian) in guardians.iter().enumerate() {
+            require!(!guardians[..i].contains(guardian), ErrorCode::InvalidGuardianConfig);
+        }
+        
+        let guardian_set = &mut ctx.accounts.guardian_set;
+        guardian_set.user_id = user_id;
+        guardian_set.guardians = guardians;
+        guardian_set.threshold = threshold;
+        guardian_set.recovery_delay = recovery_delay;
+        guardian_set.pending_recovery = None;
+        
+        msg!("가디언이 설정되었습니다. 사용자 ID: {}, 가디언: {}명, 임계값: {}, 대기 시간: {}초",
+            guardian_set.user_id, guardian_set.guardians.len(), threshold, recovery_delay);
+        Ok(())
+    }
+    
+    /// 백업 키 복구 제안 함수 (가디언만 가능)
+    /// 
+    /// 제안한 가디언의 승인이 자동으로 포함됨
+    /// 
+    /// * `new_backup_key` - 복구 후 사용할 새 백업 키
+    pub fn propose_recovery(
+        ctx: Context<GuardianOperation>,
+        new_backup_key: Pubkey,
+    ) -> Result<()> {
+        let guardian_set = &mut ctx.accounts.guardian_set;
+        let guardian = ctx.accounts.guardian.key();
         let current_time = Clock::get()?.unix_timestamp;
-        let temp_key = ctx.accounts.temp_key.key();
         
-        // 1. 철회 상태 먼저 검사 (단락 평가 최적화)
-        if key_mapping.revoked {
-            return Err(ErrorCode::RevokedKey.into());
+        require!(guardian_set.guardians.contains(&guardian), ErrorCode::NotGuardian);
+        require!(guardian_set.pending_recovery.is_none(), ErrorCode::RecoveryAlreadyPending);
+        
+        let mut pending = PendingRecovery {
+            new_backup_key,
+            approvals: vec![guardian],
+            proposed_at: current_time,
+            executable_at: 0,
+        };
+        if guardian_set.threshold <= 1 {
+            pending.executable_at = current_time.saturating_add(guardian_set.recovery_delay);
         }
+        guardian_set.pending_recovery = Some(pending);
         
-        // 2. 서명 검사 - 메인키 또는 백업키인지 먼저 확인
-        let is_main_key = temp_key == key_mapping.temp_key;
-        let is_backup_key = temp_key == key_mapping.backup_key;
+        msg!("백업 키 복구가 제안되었습니다. 사용자 ID: {}, 새 백업 키: {}, 제안자: {}",
+            guardian_set.user_id, new_backup_key, guardian);
+        Ok(())
+    }
+    
+    /// 백업 키 복구 승인 함수 (가디언만 가능)
+    /// 
+    /// 승인 수가 임계값에 도달하면 대기 시간이 시작됨
+    pub fn approve_recovery(ctx: Context<GuardianOperation>) -> Result<()> {
+        let guardian_set = &mut ctx.accounts.guardian_set;
+        let guardian = ctx.accounts.guardian.key();
+        let current_time = Clock::get()?.unix_timestamp;
         
-        if !is_main_key && !is_backup_key {
-            return Err(ErrorCode::InvalidKeySigner.into());
+        require!(guardian_set.guardians.contains(&guardian), ErrorCode::NotGuardian);
+        
+        let threshold = guardian_set.threshold as usize;
+        let recovery_delay = guardian_set.recovery_delay;
+        let pending = guardian_set
+            .pending_recovery
+            .as_mut()
+            .ok_or(ErrorCode::NoPendingRecovery)?;
+        
+        require!(!pending.approvals.contains(&guardian), ErrorCode::AlreadyApproved);
+        pending.approvals.push(guardian);
+        
+        // 임계값 도달 시 대기 시간 시작
+        if pending.approvals.len() >= threshold && pending.executable_at == 0 {
+            pending.executable_at = current_time.saturating_add(recovery_delay);
         }
         
-        // 3. 만료 시간 검증 (메인 키인 경우만)
-        if is_main_key && current_time >= key_mapping.expires_at {
-            return Err(ErrorCode::ExpiredKey.into());
+        msg!("백업 키 복구가 승인되었습니다. 승인: {}/{}, 실행 가능 시간: {}",
+            pending.approvals.len(), threshold, pending.executable_at);
+        Ok(())
+    }
+    
+    /// 백업 키 복구 실행 함수 (대기 시간 경과 후 누구나 호출 가능)
+    /// 
+    /// remaining_accounts: 세션 레지스트리의 모든 활성 KeyMapping 계정, 이어서 백업 키를 함께 교체할
+    /// 해당 사용자의 만료/철회된 KeyMapping 계정 (선택, 만료된 키 매핑도 백업 키로는 계속 사용 가능하므로 함께 교체 권장)
+    pub fn execute_recovery<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteRecovery<'info>>,
+    ) -> Result<()> {
+        let guardian_set = &mut ctx.accounts.guardian_set;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        let pending = guardian_set
+            .pending_recovery
+            .as_ref()
+            .ok_or(ErrorCode::NoPendingRecovery)?;
+        require!(
+            pending.approvals.len() >= guardian_set.threshold as usize,
+            ErrorCode::RecoveryThresholdNotMet
+        );
+        require!(
+            pending.executable_at > 0 && current_time >= pending.executable_at,
+            ErrorCode::RecoveryTimelockActive
+        );
+        let new_backup_key = pending.new_backup_key;
+        
+        // 활성 키 매핑 전체와 그 뒤에 전달된 비활성 키 매핑 분리
+        let session_registry = &ctx.accounts.session_registry;
+        let active_count = active_session_count(session_registry, current_time);
+        require!(
+            ctx.remaining_accounts.len() >= active_count,
+            ErrorCode::IncompleteSessionList
+        );
+        require!(
+            !has_duplicate_accounts(ctx.remaining_accounts),
+            ErrorCode::DuplicateSessionAccount
+        );
+        let (active_accounts, inactive_accounts) = ctx.remaining_accounts.split_at(active_count);
+        
+        let mut key_mappings = load_active_key_mappings(
+            active_accounts,
+            session_registry,
+            current_time,
+            ctx.program_id,
+        )?;
+        for account_info in inactive_accounts {
+            key_mappings.push(load_inactive_key_mapping(
+                account_info,
+                &session_registry.user_id,
+                current_time,
+                ctx.program_id,
+            )?);
         }
         
-        // 4. 보안 정책 검증 (있는 경우만)
-        if let Some(security_policy) = &mut ctx.accounts.security_policy {
-            // 일자가 바뀌었는지 체크 (단일 비교로 효율화)
-            let current_day = current_time / 86400;
-            
-            if security_policy.last_day != current_day {
-                security_policy.daily_tx_count = 0;
-                security_policy.daily_amount = 0;
-                security_policy.last_day = current_day;
-            } else {
-                // 트랜잭션 수 제한 확인
-                if security_policy.daily_tx_count >= security_policy.max_tx_per_day {
-                    return Err(ErrorCode::DailyTxLimitExceeded.into());
-                }
-                
-                // 함수 ID 허용 확인 (간소화된 체크)
-                if !security_policy.allowed_functions.is_empty() && 
-                   !security_policy.allowed_functions.contains(&function_id) {
-                    return Err(ErrorCode::FunctionNotAllowed.into());
-                }
-            }
-            
-            // 트랜잭션 카운트 증가 (한 번만 업데이트)
-            security_policy.daily_tx_count += 1;
+        for mut key_mapping in key_mappings {
+            key_mapping.backup_key = new_backup_key;
+            key_mapping.exit(ctx.program_id)?;
         }
         
-        // 5. 사용자 계정 CPI 호출 준비 (효율적인 CPI 생성)
-        // 모든 검증이 끝난 후 한 번만 CPI 호출 수행
-        let user_account_program = user_account::UserAccount::id();
-        let accounts = vec![
-            AccountMeta::new_readonly(ctx.accounts.temp_key.key(), true),
-            AccountMeta::new_readonly(user_account_program, false),
-            AccountMeta::new(key_mapping.user_account_pda, false),
-        ];
-        
-        // 데이터 필드 효율화 (함수 ID + 파라미터)
-        let mut data = vec![0u8, function_id];
-        data.extend_from_slice(&(params.len() as u32).to_le_bytes());
-        data.extend_from_slice(&params);
-        
-        // 단일 invoke 호출로 최적화
-        let ix = anchor_lang::solana_program::instruction::Instruction {
-            program_id: user_account_program,
-            accounts,
-            data,
-        };
+        guardian_set.pending_recovery = None;
         
-        invoke(
-            &ix,
-            &[
-                ctx.accounts.temp_key.to_account_info(),
-            ],
+        msg!("백업 키 복구가 완료되었습니다. 사용자 ID: {}, 새 백업 키: {}",
+            guardian_set.user_id, new_backup_key);
+        Ok(())
+    }
+    
+    /// 백업 키 복구 취소 함수 (현재 백업 키 소유자만 가능, 대기 시간 중)
+    /// 
+    /// 철회되거나 만료된 키 매핑의 백업 키로는 취소할 수 없음
+    pub fn cancel_recovery(ctx: Context<CancelRecovery>) -> Result<()> {
+        let guardian_set = &mut ctx.accounts.guardian_set;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        check_recovery_cancel_authority(
+            &ctx.accounts.backup_key_signer.key(),
+            &ctx.accounts.key_mapping,
+            current_time,
         )?;
+        require!(guardian_set.pending_recovery.is_some(), ErrorCode::NoPendingRecovery);
         
-        msg!("릴레이 트랜잭션 성공: 함수 ID {}", function_id);
+        guardian_set.pending_recovery = None;
+        
+        msg!("백업 키 복구가 취소되었습니다. 사용자 ID: {}", guardian_set.user_id);
         Ok(())
     }
-}
-
-/// 임시 키 매핑 데이터 구조체
-#[account]
-pub struct KeyMapping {
-    /// 임시 키
-    pub temp_key: Pubkey,
-    /// 백업 키 (영구적)
-    pub backup_key: Pubkey,
-    /// 사용자 ID
-    pub user_id: String,
-    /// 사용자 계정 PDA
-    pub user_account_pda: Pubkey,
-    /// 임시 키 만료 시간
-    pub expires_at: i64,
-    /// 철회 여부
-    pub revoked: bool,
-    /// 생성 시간
-    pub created_at: i64,
-}
-
-/// 수수료 정책 데이터 구조체
+    
+    /// 긴급 동결 함수 (사용자 소유자, 가디언 또는 백업 키만 가능)
+    /// 
+    /// 레지스트리에 등록된 사용자의 모든 세션 키 매핑(만료된 세션 포함)과 사용자 계정(UserAccountData)을
+    /// 즉시 동결하고, 진행 중인 동결 해제 요청이 있으면 취소함
+    /// 
+    /// remaining_accounts: 세션 레지스트리에 등록된 모든 키 매핑 계정 (만료된 세션 포함)
+    pub fn emergency_freeze<'info>(
+        ctx: Context<'_, '_, '_, 'info, EmergencyFreeze<'info>>,
+        user_id: String,
+    ) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        let session_registry = &mut ctx.accounts.session_registry;
+        
+        // 레지스트리의 모든 세션(만료되었지만 아직 정리되지 않은 세션 포함)이 전달되었는지 확인
+        let key_mappings = load_registered_key_mappings(
+            ctx.remaining_accounts,
+            session_registry,
+            ctx.program_id,
+        )?;
+        
+        // 사용자 소유자, 가디언 또는 레지스트리에 등록된 세션의 백업 키인지 확인
+        let backup_keys: Vec<Pubkey> = key_mappings.iter().map(|k| k.backup_key).collect();
+        require!(
+            is_emergency_freeze_authority(
+                &authority,
+                &ctx.accounts.user_authority,
+                ctx.accounts.guardian_set.as_deref(),
+                &backup_keys,
+            ),
+            ErrorCode::Unauthorized
+        );
+        
+        let frozen_count = key_mappings.len();
+        for mut key_mapping in key_mappings {
+            key_mapping.frozen = true;
+            key_mapping.exit(ctx.program_id)?;
+        }
+        
+        prune_expired_sessions(session_registry, current_time);
+        session_registry.frozen = true;
+        session_registry.unfreeze_at = None;
+        
+        // 사용자 계정 프로그램의 계정도 동결
+        set_user_account_freeze(
+            &ctx.accounts.relay_authority,
+            &ctx.accounts.user_account,
+            &ctx.accounts.user_account_program,
+            *ctx.bumps.get("relay_authority").unwrap(),
+            true,
+        )?;
+        
+        msg!("긴급 동결되었습니다. 사용자 ID: {}, 동결된 세션 수: {}, 요청자: {}",
+            user_id, frozen_count, authority);
+        Ok(())
+    }
+    
+    /// 동결 해제 요청 함수 (사용자 소유자만 가능)
+    /// 
+    /// UNFREEZE_DELAY 이후 `unfreeze`로 해제할 수 있으며, 그 전에 다시 긴급 동결되면 요청은 취소됨
+    pub fn request_unfreeze(ctx: Context<RequestUnfreeze>, user_id: String) -> Result<()> {
+        let session_registry = &mut ctx.accounts.session_registry;
+        require!(session_registry.frozen, ErrorCode::NotFrozen);
+        
+        let unfreeze_at = Clock::get()?.unix_timestamp.saturating_add(UNFREEZE_DELAY);
+        session_registry.unfreeze_at = Some(unfreeze_at);
+        
+        msg!("동결 해제가 요청되었습니다. 사용자 ID: {}, 해제 가능 시간: {}", user_id, unfreeze_at);
+        Ok(())
+    }
+    
+    /// 동결 해제 함수 (사용자 소유자만 가능, 해제 요청 후 대기 시간 경과 필요)
+    /// 
+    /// remaining_accounts: 세션 레지스트리에 등록된 모든 키 매핑 계정 (만료된 세션 포함)
+    pub fn unfreeze<'info>(
+        ctx: Context<'_, '_, '_, 'info, Unfreeze<'info>>,
+        user_id: String,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let session_registry = &mut ctx.accounts.session_registry;
+        
+        require!(session_registry.frozen, ErrorCode::NotFrozen);
+        match session_registry.unfreeze_at {
+            Some(unfreeze_at) => require!(
+                current_time >= unfreeze_at,
+                ErrorCode::UnfreezeTimelockActive
+            ),
+            None => return Err(ErrorCode::UnfreezeNotRequested.into()),
+        }
+        
+        // 긴급 동결과 같이 레지스트리의 모든 세션을 해제한 뒤 만료된 세션 정리
+        let key_mappings = load_registered_key_mappings(
+            ctx.remaining_accounts,
+            session_registry,
+            ctx.program_id,
+        )?;
+        for mut key_mapping in key_mappings {
+            key_mapping.frozen = false;
+            key_mapping.exit(ctx.program_id)?;
+        }
+        
+        prune_expired_sessions(session_registry, current_time);
+        session_registry.frozen = false;
+        session_registry.unfreeze_at = None;
+        
+        set_user_account_freeze(
+            &ctx.accounts.relay_authority,
+            &ctx.accounts.user_account,
+            &ctx.accounts.user_account_program,
+            *ctx.bumps.get("relay_authority").unwrap(),
+            false,
+        )?;
+        
+        msg!("동결이 해제되었습니다. 사용자 ID: {}", user_id);
+        Ok(())
+    }
+    
+    /// 세션 만료 시간 연장 함수 (백업 키 또는 사용자 소유자만 가능)
+    /// 
+    /// * `new_expires_at` - 새로운 임시 키 만료 시간
+    pub fn extend_session(
+        ctx: Context<ExtendSession>,
+        new_expires_at: i64,
+    ) -> Result<()> {
+        let key_mapping = &mut ctx.accounts.key_mapping;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require!(!key_mapping.revoked, ErrorCode::RevokedKey);
+        require!(
+            is_backup_or_owner(
+                &ctx.accounts.authority.key(),
+                key_mapping,
+                &ctx.accounts.user_authority,
+            ),
+            ErrorCode::Unauthorized
+        );
+        
+        // 현재 만료 시간보다 이후로만 연장 가능
+        require!(
+            new_expires_at > key_mapping.expires_at && new_expires_at > current_time,
+            ErrorCode::InvalidExpiry
+        );
+        
+        // 동결 중에는 연장 불가 (동결 해제 시 전달되지 않은 만료 세션의 동결 표시도 여기서 정리)
+        require!(!ctx.accounts.session_registry.frozen, ErrorCode::SessionFrozen);
+        key_mapping.frozen = false;
+        key_mapping.expires_at = new_expires_at;
+        
+        // 세션 레지스트리의 만료 시간도 갱신
+        let session_registry = &mut ctx.accounts.session_registry;
+        match session_registry
+            .sessions
+            .iter_mut()
+            .find(|s| s.temp_key == key_mapping.temp_key)
+        {
+            Some(entry) => entry.expires_at = new_expires_at,
+            None => return Err(ErrorCode::SessionNotFound.into()),
+        }
+        
+        msg!("세션이 연장되었습니다. 임시 키: {}, 새 만료 시간: {}",
+            key_mapping.temp_key, new_expires_at);
+        Ok(())
+    }
+    
+    /// 임시 키 교체 함수 (백업 키 또는 사용자 소유자만 가능)
+    /// 
+    /// 기존 키 매핑을 새 임시 키의 PDA로 이전하고 사용량 상태를 유지한 뒤 기존 계정을 닫음
+    pub fn rotate_temp_key(ctx: Context<RotateTempKey>) -> Result<()> {
+        let old_key_mapping = &ctx.accounts.key_mapping;
+        
+        require!(!old_key_mapping.revoked, ErrorCode::RevokedKey);
+        require!(
+            is_backup_or_owner(
+                &ctx.accounts.authority.key(),
+                old_key_mapping,
+                &ctx.accounts.user_authority,
+            ),
+            ErrorCode::Unauthorized
+        );
+        
+        require!(!ctx.accounts.session_registry.frozen, ErrorCode::SessionFrozen);
+        
+        let old_temp_key = old_key_mapping.temp_key;
+        let new_temp_key = ctx.accounts.new_temp_key.key();
+        
+        // 사용량 상태를 유지한 채 새 임시 키로 이전
+        let mut moved = (**old_key_mapping).clone();
+        moved.temp_key = new_temp_key;
+        moved.payer = ctx.accounts.payer.key();
+        moved.frozen = false;
+        ctx.accounts.new_key_mapping.set_inner(moved);
+        
+        // 세션 레지스트리의 임시 키 갱신
+        let session_registry = &mut ctx.accounts.session_registry;
+        match session_registry
+            .sessions
+            .iter_mut()
+            .find(|s| s.temp_key == old_temp_key)
+        {
+            Some(entry) => entry.temp_key = new_temp_key,
+            None => return Err(ErrorCode::SessionNotFound.into()),
+        }
+        
+        msg!("임시 키가 교체되었습니다. 이전: {}, 새로운: {}", old_temp_key, new_temp_key);
+        Ok(())
+    }
+    
+    /// 철회되었거나 만료된 키 매핑 계정을 닫고 렌트를 원래 지불자에게 반환
+    /// 
+    /// 백업 키, 사용자 소유자 또는 원래 지불자만 호출 가능
+    pub fn close_key_mapping(ctx: Context<CloseKeyMapping>) -> Result<()> {
+        let key_mapping = &ctx.accounts.key_mapping;
+        let current_time = Clock::get()?.unix_timestamp;
+        let authority = ctx.accounts.authority.key();
+        
+        require!(
+            authority == key_mapping.payer
+                || is_backup_or_owner(&authority, key_mapping, &ctx.accounts.user_authority),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            is_key_mapping_closable(key_mapping, current_time),
+            ErrorCode::SessionStillActive
+        );
+        
+        remove_session(&mut ctx.accounts.session_registry, &key_mapping.temp_key);
+        
+        msg!("키 매핑이 닫혔습니다. 임시 키: {}, 렌트 반환: {}",
+            key_mapping.temp_key, key_mapping.payer);
+        Ok(())
+    }
+    
+    /// 철회되었거나 만료된 키 매핑 일괄 정리 함수 (누구나 호출 가능한 크랭크)
+    /// 
+    /// remaining_accounts로 (키 매핑, 원래 지불자) 쌍을 순서대로 전달
+    /// 세션 레지스트리의 만료 항목은 다음 등록 또는 일괄 철회 시 정리됨
+    pub fn close_expired_key_mappings<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseExpiredKeyMappings<'info>>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        let pairs = ctx.remaining_accounts.chunks_exact(2);
+        require!(pairs.remainder().is_empty(), ErrorCode::InvalidRemainingAccounts);
+        
+        let mut closed_count = 0u32;
+        for pair in pairs {
+            let key_mapping_info = &pair[0];
+            let payer_info = &pair[1];
+            let key_mapping: Account<KeyMapping> = Account::try_from(key_mapping_info)?;
+            
+            let (expected_pda, _) = Pubkey::find_program_address(
+                &[b"key_mapping", key_mapping.temp_key.as_ref()],
+                ctx.program_id,
+            );
+            require!(key_mapping_info.key() == expected_pda, ErrorCode::SessionNotFound);
+            require!(payer_info.key() == key_mapping.payer, ErrorCode::PayerMismatch);
+            require!(
+                is_key_mapping_closable(&key_mapping, current_time),
+                ErrorCode::SessionStillActive
+            );
+            
+            key_mapping.close(payer_info.clone())?;
+            closed_count += 1;
+        }
+        
+        msg!("만료/철회된 키 매핑 {}개가 정리되었습니다", closed_count);
+        Ok(())
+    }
+    
+    /// SPL 토큰 전송 함수
+    /// 
+    /// * `amount` - 전송 금액
+    /// * `nonce` - 키 매핑의 현재 논스 (재전송 방지, 사용 후 1 증가)
+    /// * `deadline` - 요청 유효 기한 (Unix timestamp)
+    /// 
+    /// remaining_accounts: 수수료 분배 대상이 설정된 경우 대상 순서대로 각 대상 지갑 소유의 토큰 계정
+    pub fn transfer_spl_token<'info>(
+        ctx: Context<'_, '_, '_, 'info, TransferSplToken<'info>>,
+        amount: u64,
+        nonce: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        // 릴레이 전역 일시중지 확인
+        require!(!ctx.accounts.relay_config.paused, ErrorCode::RelayPaused);
+        
+        // 재전송 방지 (논스 및 기한 검증)
+        consume_nonce(&mut ctx.accounts.key_mapping, nonce, deadline, current_time)?;
+        
+        let key_mapping = &ctx.accounts.key_mapping;
+        let temp_key = ctx.accounts.temp_key.key();
+        
+        // 철회 및 긴급 동결 확인
+        require!(!key_mapping.revoked, ErrorCode::RevokedKey);
+        require!(!key_mapping.frozen, ErrorCode::SessionFrozen);
+        
+        // 서명자가 주 임시 키인지 백업 키인지 확인
+        let is_main_key = temp_key == key_mapping.temp_key;
+        let is_backup_key = temp_key == key_mapping.backup_key;
+        
+        require!(
+            is_main_key || is_backup_key,
+            ErrorCode::InvalidKeySigner
+        );
+        
+        // 수수료 계산 (수신자 수령액과 한도 검증 금액은 수수료 부담 방식에 따라 결정)
+        let mut recipient_amount = amount;
+        let mut debit_amount = amount;
+        let fee_policy = &ctx.accounts.fee_policy;
+        
+        // 사용자의 30일 거래량 조회
+        let volume_tracker = &mut ctx.accounts.volume_tracker;
+        let volume = refresh_volume_tracker(
+            volume_tracker,
+            &key_mapping.user_id,
+            ctx.accounts.mint.key(),
+            current_time,
+        );
+        
+        // 토큰별 수수료율 또는 기본 토큰 수수료율을 거래량 구간에 따라 적용
+        let fee_bps = token_fee_bps(
+            fee_policy,
+            ctx.accounts.token_fee_policy.as_deref(),
+            volume,
+        );
+        
+        let fee_amount = calculate_fee(amount, fee_bps, fee_policy)?;
+        match fee_policy.fee_mode {
+            FeeMode::Deduct => recipient_amount = amount - fee_amount,
+            FeeMode::SenderPays => debit_amount = amount.saturating_add(fee_amount),
+        }
+        record_volume(volume_tracker, amount, current_time);
+        
+        msg!("수수료 정보: 금액 = {}, 수수료 = {}, 수신자 수령액 = {}", 
+            amount, fee_amount, recipient_amount);
+        
+        // 임시 키인 경우 만료 시간 및 세션 권한 범위 확인 (송신자 부담 수수료 포함)
+        if is_main_key {
+            require!(
+                current_time < key_mapping.expires_at,
+                ErrorCode::SessionExpired
+            );
+            
+            check_session_scope(
+                key_mapping,
+                &ctx.accounts.token_program.key(),
+                FUNCTION_TRANSFER_SPL_TOKEN,
+                debit_amount,
+            )?;
+            
+            // 수신자 정책 확인 (토큰 계정 또는 토큰 계정 소유자 지갑 기준)
+            if let Some(recipient_policy) =
+                load_recipient_policy(&ctx.accounts.recipient_policy, ctx.program_id)?
+            {
+                check_recipient(
+                    &recipient_policy,
+                    &[ctx.accounts.to.key(), ctx.accounts.to.owner],
+                )?;
+            }
+        }
+        
+        // 보안 정책 검증 (송신자 부담 수수료 포함)
+        if let Some(mut security_policy) =
+            load_security_policy(&ctx.accounts.security_policy, ctx.program_id)?
+        {
+            apply_security_policy(
+                &mut security_policy,
+                &ctx.accounts.token_program.key(),
+                FUNCTION_TRANSFER_SPL_TOKEN,
+                Some(ctx.accounts.from.mint),
+                debit_amount,
+                current_time,
+            )?;
+            security_policy.exit(ctx.program_id)?;
+        }
+        
+        // 수수료 전송 (수수료가 있는 경우)
+        if fee_amount > 0 && fee_policy.fee_recipients.is_empty() {
+            // 토큰 계정에서 수수료 수령자에게 수수료 전송
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.from.to_account_info(),
+                to: ctx.accounts.fee_collector.to_account_info(),
+                authority: ctx.accounts.temp_key.to_account_info(),
+            };
+            
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+            
+            token::transfer(cpi_ctx, fee_amount)?;
+        } else if fee_amount > 0 {
+            // 분배 대상별 토큰 계정으로 수수료 분배
+            require!(
+                ctx.remaining_accounts.len() == fee_policy.fee_recipients.len(),
+                ErrorCode::FeeRecipientMismatch
+            );
+            let shares = split_fee(fee_amount, &fee_policy.fee_recipients, fee_policy.remainder_index);
+            
+            for ((fee_recipient, share), recipient_info) in fee_policy
+                .fee_recipients
+                .iter()
+                .zip(shares)
+                .zip(ctx.remaining_accounts.iter())
+            {
+                let recipient_token_account = Account::<TokenAccount>::try_from(recipient_info)?;
+                require!(
+                    recipient_token_account.owner == fee_recipient.recipient
+                        && recipient_token_account.mint == ctx.accounts.mint.key(),
+                    ErrorCode::FeeRecipientMismatch
+                );
+                
+                if share > 0 {
+                    let cpi_accounts = Transfer {
+                        from: ctx.accounts.from.to_account_info(),
+                        to: recipient_info.clone(),
+                        authority: ctx.accounts.temp_key.to_account_info(),
+                    };
+                    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+                    token::transfer(cpi_ctx, share)?;
+                }
+            }
+        }
+        
+        // 토큰 전송 (차감 방식이면 수수료를 제외한 금액, 송신자 부담 방식이면 요청 금액 그대로)
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.from.to_account_info(),
+            to: ctx.accounts.to.to_account_info(),
+            authority: ctx.accounts.temp_key.to_account_info(),
+        };
+        
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        
+        token::transfer(cpi_ctx, recipient_amount)?;
+        
+        // 세션 사용량 및 사용 내역 기록
+        let mint = ctx.accounts.mint.key();
+        record_session_use(
+            &mut ctx.accounts.key_mapping,
+            is_main_key,
+            FUNCTION_TRANSFER_SPL_TOKEN,
+            Some(mint),
+            debit_amount,
+            current_time,
+        );
+        
+        // 페이마스터 후원 (있는 경우 수수료 지불자에게 호출당 비용 보전)
+        reimburse_fee_payer(
+            ctx.accounts.paymaster.as_mut(),
+            ctx.accounts.paymaster_usage.as_mut(),
+            &ctx.accounts.fee_payer.to_account_info(),
+            &ctx.accounts.key_mapping.user_id,
+            current_time,
+        )?;
+        
+        msg!("SPL 토큰 전송이 완료되었습니다. 수신자: {}, 금액: {}", 
+            ctx.accounts.to.key(), recipient_amount);
+        Ok(())
+    }
+    
+    /// SOL 전송 함수
+    /// 
+    /// 임시 키 또는 백업 키 지갑의 lamports를 수신자에게 전송하고,
+    /// 수수료 정책의 `sol_fee_bps`(최소 `min_fee_amount`) 수수료를 수수료 수금자에게 전송 (수수료 정책 필수)
+    /// 
+    /// * `amount` - 전송 금액 (lamports, 수수료 포함)
+    /// * `nonce` - 키 매핑의 현재 논스 (재전송 방지, 사용 후 1 증가)
+    /// * `deadline` - 요청 유효 기한 (Unix timestamp)
+    /// 
+    /// remaining_accounts: 수수료 분배 대상이 설정된 경우 대상 순서대로 각 대상 지갑
+    pub fn transfer_sol<'info>(
+        ctx: Context<'_, '_, '_, 'info, TransferSol<'info>>,
+        amount: u64,
+        nonce: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let native_mint = token::spl_token::native_mint::ID;
+        
+        // 릴레이 전역 일시중지 확인
+        require!(!ctx.accounts.relay_config.paused, ErrorCode::RelayPaused);
+        
+        // 재전송 방지 (논스 및 기한 검증)
+        consume_nonce(&mut ctx.accounts.key_mapping, nonce, deadline, current_time)?;
+        
+        let key_mapping = &ctx.accounts.key_mapping;
+        let temp_key = ctx.accounts.temp_key.key();
+        
+        // 철회 및 긴급 동결 확인
+        require!(!key_mapping.revoked, ErrorCode::RevokedKey);
+        require!(!key_mapping.frozen, ErrorCode::SessionFrozen);
+        
+        // 서명자가 주 임시 키인지 백업 키인지 확인
+        let is_main_key = temp_key == key_mapping.temp_key;
+        let is_backup_key = temp_key == key_mapping.backup_key;
+        
+        require!(
+            is_main_key || is_backup_key,
+            ErrorCode::InvalidKeySigner
+        );
+        
+        // 수수료 계산 (수신자 수령액과 한도 검증 금액은 수수료 부담 방식에 따라 결정)
+        let mut recipient_amount = amount;
+        let mut debit_amount = amount;
+        let fee_policy = &ctx.accounts.fee_policy;
+        
+        // 사용자의 30일 SOL 거래량에 따른 수수료율 적용
+        let volume_tracker = &mut ctx.accounts.volume_tracker;
+        let volume = refresh_volume_tracker(
+            volume_tracker,
+            &key_mapping.user_id,
+            native_mint,
+            current_time,
+        );
+        let fee_bps = sol_fee_bps(fee_policy, volume);
+        
+        let fee_amount = calculate_fee(amount, fee_bps, fee_policy)?;
+        match fee_policy.fee_mode {
+            FeeMode::Deduct => recipient_amount = amount - fee_amount,
+            FeeMode::SenderPays => debit_amount = amount.saturating_add(fee_amount),
+        }
+        record_volume(volume_tracker, amount, current_time);
+        
+        msg!("수수료 정보: 금액 = {}, 수수료 = {}, 수신자 수령액 = {}",
+            amount, fee_amount, recipient_amount);
+        
+        // 임시 키인 경우 만료 시간, 세션 권한 범위(송신자 부담 수수료 포함) 및 수신자 정책 확인
+        if is_main_key {
+            require!(
+                current_time < key_mapping.expires_at,
+                ErrorCode::SessionExpired
+            );
+            
+            check_session_scope(
+                key_mapping,
+                &system_program::ID,
+                FUNCTION_TRANSFER_SOL,
+                debit_amount,
+            )?;
+            
+            if let Some(recipient_policy) =
+                load_recipient_policy(&ctx.accounts.recipient_policy, ctx.program_id)?
+            {
+                check_recipient(&recipient_policy, &[ctx.accounts.to.key()])?;
+            }
+        }
+        
+        // 보안 정책 검증 (SOL은 네이티브 Mint 기준으로 토큰별 한도 적용, 송신자 부담 수수료 포함)
+        if let Some(mut security_policy) =
+            load_security_policy(&ctx.accounts.security_policy, ctx.program_id)?
+        {
+            apply_security_policy(
+                &mut security_policy,
+                &system_program::ID,
+                FUNCTION_TRANSFER_SOL,
+                Some(native_mint),
+                debit_amount,
+                current_time,
+            )?;
+            security_policy.exit(ctx.program_id)?;
+        }
+        
+        // 수수료 전송
+        let fee_policy = &ctx.accounts.fee_policy;
+        if fee_amount > 0 && fee_policy.fee_recipients.is_empty() {
+            let cpi_ctx = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.temp_key.to_account_info(),
+                    to: ctx.accounts.fee_collector.to_account_info(),
+                },
+            );
+            anchor_lang::system_program::transfer(cpi_ctx, fee_amount)?;
+        } else if fee_amount > 0 {
+            // 분배 대상 지갑별로 수수료 분배
+            require!(
+                ctx.remaining_accounts.len() == fee_policy.fee_recipients.len(),
+                ErrorCode::FeeRecipientMismatch
+            );
+            let shares = split_fee(fee_amount, &fee_policy.fee_recipients, fee_policy.remainder_index);
+            
+            for ((fee_recipient, share), recipient_info) in fee_policy
+                .fee_recipients
+                .iter()
+                .zip(shares)
+                .zip(ctx.remaining_accounts.iter())
+            {
+                require_keys_eq!(
+                    recipient_info.key(),
+                    fee_recipient.recipient,
+                    ErrorCode::FeeRecipientMismatch
+                );
+                
+                if share > 0 {
+                    let cpi_ctx = CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        anchor_lang::system_program::Transfer {
+                            from: ctx.accounts.temp_key.to_account_info(),
+                            to: recipient_info.clone(),
+                        },
+                    );
+                    anchor_lang::system_program::transfer(cpi_ctx, share)?;
+                }
+            }
+        }
+        
+        // SOL 전송 (차감 방식이면 수수료를 제외한 금액, 송신자 부담 방식이면 요청 금액 그대로)
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.temp_key.to_account_info(),
+                to: ctx.accounts.to.to_account_info(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_ctx, recipient_amount)?;
+        
+        // 세션 사용량 및 사용 내역 기록
+        record_session_use(
+            &mut ctx.accounts.key_mapping,
+            is_main_key,
+            FUNCTION_TRANSFER_SOL,
+            Some(native_mint),
+            debit_amount,
+            current_time,
+        );
+        
+        // 페이마스터 후원 (있는 경우 수수료 지불자에게 호출당 비용 보전)
+        reimburse_fee_payer(
+            ctx.accounts.paymaster.as_mut(),
+            ctx.accounts.paymaster_usage.as_mut(),
+            &ctx.accounts.fee_payer.to_account_info(),
+            &ctx.accounts.key_mapping.user_id,
+            current_time,
+        )?;
+        
+        msg!("SOL 전송이 완료되었습니다. 수신자: {}, 금액: {} lamports",
+            ctx.accounts.to.key(), recipient_amount);
+        Ok(())
+    }
+    
+    /// 트랜잭션 릴레이 함수
+    /// 임시 키나 백업 키를 사용하여 유저 계정 프로그램 호출
+    /// 
+    /// * `function_id` - 호출할 함수 ID
+    /// * `params` - 함수에 전달할 파라미터
+    /// * `nonce` - 키 매핑의 현재 논스 (재전송 방지, 사용 후 1 증가)
+    /// * `deadline` - 요청 유효 기한 (Unix timestamp)
+    pub fn relay_transaction(
+        ctx: Context<RelayTransaction>,
+        function_id: u8,
+        params: Vec<u8>,
+        nonce: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        let key_mapping = &mut ctx.accounts.key_mapping;
+        let current_time = Clock::get()?.unix_timestamp;
+        let temp_key = ctx.accounts.temp_key.key();
+        
+        // 릴레이 전역 일시중지 확인
+        require!(!ctx.accounts.relay_config.paused, ErrorCode::RelayPaused);
+        
+        // 1. 철회 상태 먼저 검사 (단락 평가 최적화)
+        if key_mapping.revoked {
+            return Err(ErrorCode::RevokedKey.into());
+        }
+        
+        // 긴급 동결 확인
+        require!(!key_mapping.frozen, ErrorCode::SessionFrozen);
+        
+        // 재전송 방지 (논스 및 기한 검증)
+        consume_nonce(key_mapping, nonce, deadline, current_time)?;
+        
+        // 2. 서명 검사 - 메인키 또는 백업키인지 먼저 확인
+        let is_main_key = temp_key == key_mapping.temp_key;
+        let is_backup_key = temp_key == key_mapping.backup_key;
+        
+        if !is_main_key && !is_backup_key {
+            return Err(ErrorCode::InvalidKeySigner.into());
+        }
+        
+        // 3. 만료 시간 검증 (메인 키인 경우만)
+        if is_main_key && current_time >= key_mapping.expires_at {
+            return Err(ErrorCode::ExpiredKey.into());
+        }
+        
+        // 라우팅 대상 프로그램 검증 (함수 ID로 결정되며 호출자가 지정할 수 없음)
+        let routed_program = routed_program(function_id)?;
+        require_keys_eq!(
+            ctx.accounts.target_program.key(),
+            routed_program,
+            ErrorCode::TargetProgramMismatch
+        );
+        
+        // 세션 권한 범위 검증 (메인 키인 경우만)
+        let amount = relayed_amount(function_id, &params);
+        if is_main_key {
+            check_session_scope(
+                key_mapping,
+                &routed_program,
+                function_id,
+                amount,
+            )?;
+            
+            // 수신자 정책 확인 (전송 함수인 경우)
+            if let Some(recipient) = relayed_recipient(function_id, &params) {
+                if let Some(recipient_policy) =
+                    load_recipient_policy(&ctx.accounts.recipient_policy, ctx.program_id)?
+                {
+                    check_recipient(&recipient_policy, &[recipient])?;
+                }
+            }
+        }
+        
+        // 4. 보안 정책 검증 (있는 경우만)
+        if let Some(mut security_policy) =
+            load_security_policy(&ctx.accounts.security_policy, ctx.program_id)?
+        {
+            let mint = relayed_mint(function_id, &params);
+            apply_security_policy(
+                &mut security_policy,
+                &routed_program,
+                function_id,
+                mint,
+                amount,
+                current_time,
+            )?;
+            security_policy.exit(ctx.program_id)?;
+        }
+        
+        // 5. 사용자 계정 CPI 호출 (모든 검증이 끝난 후 한 번만 수행)
+        let ix = user_account_instruction(
+            &temp_key,
+            &key_mapping.user_account_pda,
+            function_id,
+            &params,
+        );
+        
+        invoke(
+            &ix,
+            &[
+                ctx.accounts.temp_key.to_account_info(),
+                ctx.accounts.aa_relay_program.to_account_info(),
+                ctx.accounts.user_account.to_account_info(),
+                ctx.accounts.target_program.to_account_info(),
+            ],
+        )?;
+        
+        // 세션 사용량 및 사용 내역 기록
+        record_session_use(
+            key_mapping,
+            is_main_key,
+            function_id,
+            relayed_session_mint(function_id, &params),
+            amount,
+            current_time,
+        );
+        
+        // 페이마스터 후원 (있는 경우 수수료 지불자에게 호출당 비용 보전)
+        reimburse_fee_payer(
+            ctx.accounts.paymaster.as_mut(),
+            ctx.accounts.paymaster_usage.as_mut(),
+            &ctx.accounts.fee_payer.to_account_info(),
+            &key_mapping.user_id,
+            current_time,
+        )?;
+        
+        msg!("릴레이 트랜잭션 성공: 함수 ID {}", function_id);
+        Ok(())
+    }
+    
+    /// 서명된 인텐트 릴레이 함수 (가스리스 메타 트랜잭션)
+    /// 
+    /// 임시 키는 트랜잭션에 서명하지 않고, 같은 트랜잭션의 직전 명령어인 ed25519 프로그램
+    /// 명령어로 (함수 ID, 파라미터, 논스, 기한)에 서명함. 제3자 릴레이어가 트랜잭션을
+    /// 구성하고 수수료를 지불함
+    /// 
+    /// * `function_id` - 호출할 함수 ID
+    /// * `params` - 함수에 전달할 파라미터
+    /// * `nonce` - 키 매핑의 현재 논스 (사용 후 1 증가)
+    /// * `deadline` - 인텐트 유효 기한 (Unix timestamp)
+    pub fn relay_signed_intent(
+        ctx: Context<RelaySignedIntent>,
+        function_id: u8,
+        params: Vec<u8>,
+        nonce: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let temp_key = ctx.accounts.temp_key.key();
+        
+        // 릴레이 전역 일시중지 확인
+        require!(!ctx.accounts.relay_config.paused, ErrorCode::RelayPaused);
+        
+        // 1. ed25519 서명 검증 (인텐트 메시지가 임시 키로 서명되었는지 확인)
+        let message = intent_message(
+            ctx.program_id,
+            &ctx.accounts.target_program.key(),
+            function_id,
+            &params,
+            nonce,
+            deadline,
+        );
+        verify_ed25519_instruction(&ctx.accounts.instructions_sysvar, &temp_key, &message)?;
+        
+        // 2. 기한 및 논스 검증
+        let key_mapping = &mut ctx.accounts.key_mapping;
+        consume_nonce(key_mapping, nonce, deadline, current_time)?;
+        
+        // 3. 철회, 동결 및 만료 검증
+        require!(!key_mapping.revoked, ErrorCode::RevokedKey);
+        require!(!key_mapping.frozen, ErrorCode::SessionFrozen);
+        require!(current_time < key_mapping.expires_at, ErrorCode::ExpiredKey);
+        
+        // 4. 라우팅 대상 프로그램 및 세션 권한 범위 검증
+        let routed_program = routed_program(function_id)?;
+        require_keys_eq!(
+            ctx.accounts.target_program.key(),
+            routed_program,
+            ErrorCode::TargetProgramMismatch
+        );
+        
+        let amount = relayed_amount(function_id, &params);
+        check_session_scope(
+            key_mapping,
+            &routed_program,
+            function_id,
+            amount,
+        )?;
+        
+        // 수신자 정책 확인 (전송 함수인 경우)
+        if let Some(recipient) = relayed_recipient(function_id, &params) {
+            if let Some(recipient_policy) =
+                load_recipient_policy(&ctx.accounts.recipient_policy, ctx.program_id)?
+            {
+                check_recipient(&recipient_policy, &[recipient])?;
+            }
+        }
+        
+        // 5. 보안 정책 검증 (있는 경우만)
+        if let Some(mut security_policy) =
+            load_security_policy(&ctx.accounts.security_policy, ctx.program_id)?
+        {
+            let mint = relayed_mint(function_id, &params);
+            apply_security_policy(
+                &mut security_policy,
+                &routed_program,
+                function_id,
+                mint,
+                amount,
+                current_time,
+            )?;
+            security_policy.exit(ctx.program_id)?;
+        }
+        
+        // 6. 키 매핑 PDA를 서명자로 사용자 계정 CPI 호출
+        let ix = user_account_instruction(
+            &key_mapping.key(),
+            &key_mapping.user_account_pda,
+            function_id,
+            &params,
+        );
+        let bump = *ctx.bumps.get("key_mapping").unwrap();
+        
+        invoke_signed(
+            &ix,
+            &[
+                key_mapping.to_account_info(),
+                ctx.accounts.aa_relay_program.to_account_info(),
+                ctx.accounts.user_account.to_account_info(),
+                ctx.accounts.target_program.to_account_info(),
+            ],
+            &[&[b"key_mapping", temp_key.as_ref(), &[bump]]],
+        )?;
+        
+        // 세션 사용량 및 사용 내역 기록 (인텐트는 항상 주 임시 키 서명)
+        record_session_use(
+            key_mapping,
+            true,
+            function_id,
+            relayed_session_mint(function_id, &params),
+            amount,
+            current_time,
+        );
+        
+        // 페이마스터 후원 (있는 경우 수수료 지불자에게 호출당 비용 보전)
+        reimburse_fee_payer(
+            ctx.accounts.paymaster.as_mut(),
+            ctx.accounts.paymaster_usage.as_mut(),
+            &ctx.accounts.fee_payer.to_account_info(),
+            &key_mapping.user_id,
+            current_time,
+        )?;
+        
+        msg!("서명된 인텐트 릴레이 성공: 함수 ID {}, 논스 {}", function_id, nonce);
+        Ok(())
+    }
+}
+
+/// 릴레이 파라미터에서 이동 금액 추출
+/// 
+/// 함수 ID 0(토큰 전송)은 params[0..8], 함수 ID 2(스왑)는 params[64..72]의 금액을 사용
+fn relayed_amount(function_id: u8, params: &[u8]) -> u64 {
+    match function_id {
+        FUNCTION_TRANSFER if params.len() >= 8 => {
+            u64::from_le_bytes(params[0..8].try_into().unwrap())
+        }
+        FUNCTION_CREATE_SWAP if params.len() >= 80 => {
+            u64::from_le_bytes(params[64..72].try_into().unwrap())
+        }
+        _ => 0,
+    }
+}
+
+/// 릴레이된 함수 파라미터에서 수신자 추출
+/// 
+/// * 함수 ID 0 (전송): params[8..40] = recipient
+fn relayed_recipient(function_id: u8, params: &[u8]) -> Option<Pubkey> {
+    match function_id {
+        FUNCTION_TRANSFER if params.len() >= 40 => {
+            Some(Pubkey::new_from_array(params[8..40].try_into().unwrap()))
+        }
+        _ => None,
+    }
+}
+
+/// 릴레이된 함수 파라미터에서 전송되는 토큰 Mint 추출
+/// 
+/// * 함수 ID 0 (전송): params[40..72] = token_mint (없으면 SOL 전송)
+/// * 함수 ID 2 (스왑): params[0..32] = token_a (지출되는 토큰)
+fn relayed_mint(function_id: u8, params: &[u8]) -> Option<Pubkey> {
+    match function_id {
+        FUNCTION_TRANSFER if params.len() >= 72 => {
+            Some(Pubkey::new_from_array(params[40..72].try_into().unwrap()))
+        }
+        FUNCTION_CREATE_SWAP if params.len() >= 80 => {
+            Some(Pubkey::new_from_array(params[0..32].try_into().unwrap()))
+        }
+        _ => None,
+    }
+}
+
+/// 세션 사용 내역에 기록할 토큰 Mint 결정
+/// 
+/// 토큰 Mint가 없는 전송(함수 ID 0)은 SOL 전송이므로 네이티브 Mint로 기록
+fn relayed_session_mint(function_id: u8, params: &[u8]) -> Option<Pubkey> {
+    match relayed_mint(function_id, params) {
+        Some(mint) => Some(mint),
+        None if function_id == FUNCTION_TRANSFER => Some(token::spl_token::native_mint::ID),
+        None => None,
+    }
+}
+
+/// 릴레이 함수 ID가 실제로 실행되는 프로그램
+/// 
+/// 사용자 계정 프로그램은 함수 ID 0~2를 내부에서 처리하므로, 세션 권한 범위와 호출 허용 목록은
+/// 호출자가 전달한 대상 프로그램이 아닌 이 값으로 검증함
+fn routed_program(function_id: u8) -> std::result::Result<Pubkey, ErrorCode> {
+    match function_id {
+        FUNCTION_TRANSFER | FUNCTION_REGISTER_TOKEN | FUNCTION_CREATE_SWAP => {
+            Ok(user_account::UserAccount::id())
+        }
+        _ => Err(ErrorCode::InvalidFunctionId),
+    }
+}
+
+/// 사용자 계정 프로그램 execute_transaction 호출 명령어 생성
+/// 
+/// 계정 순서는 사용자 계정 프로그램의 ExecuteTransaction과 같음 (서명자, 호출자 프로그램, 사용자 계정).
+/// 호출자 프로그램은 사용자 계정에 등록된 AA 릴레이 프로그램 ID와 비교되므로 이 프로그램 ID를 전달함
+fn user_account_instruction(
+    signer: &Pubkey,
+    user_account_pda: &Pubkey,
+    function_id: u8,
+    params: &[u8],
+) -> Instruction {
+    // Anchor 명령어 식별자 (sha256("global:execute_transaction")의 앞 8바이트) + function_id + params(Vec<u8>)
+    let mut data = hash(b"global:execute_transaction").to_bytes()[..8].to_vec();
+    data.push(function_id);
+    data.extend_from_slice(&(params.len() as u32).to_le_bytes());
+    data.extend_from_slice(params);
+    
+    Instruction {
+        program_id: user_account::UserAccount::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new_readonly(crate::ID, false),
+            AccountMeta::new(*user_account_pda, false),
+        ],
+        data,
+    }
+}
+
+/// 서명된 인텐트 메시지 생성
+/// 
+/// 도메인 구분자 + AA 릴레이 프로그램 ID + 대상 프로그램 ID + 함수 ID + 파라미터 길이(u32)
+/// + 파라미터 + 논스(u64) + 기한(i64), 리틀 엔디언
+/// 
+/// 프로그램 ID를 포함하므로 다른 배포본이나 다른 대상 프로그램으로 서명을 재사용할 수 없음
+pub fn intent_message(
+    relay_program: &Pubkey,
+    target_program: &Pubkey,
+    function_id: u8,
+    params: &[u8],
+    nonce: u64,
+    deadline: i64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(INTENT_DOMAIN.len() + 64 + 1 + 4 + params.len() + 16);
+    message.extend_from_slice(INTENT_DOMAIN);
+    message.extend_from_slice(relay_program.as_ref());
+    message.extend_from_slice(target_program.as_ref());
+    message.push(function_id);
+    message.extend_from_slice(&(params.len() as u32).to_le_bytes());
+    message.extend_from_slice(params);
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message.extend_from_slice(&deadline.to_le_bytes());
+    message
+}
+
+/// 직전 명령어가 지정된 공개키와 메시지에 대한 ed25519 서명 검증 명령어인지 확인
+/// 
+/// 서명 검증 자체는 ed25519 프로그램이 수행하며, 여기서는 검증 대상(공개키, 메시지)이
+/// 같은 명령어 데이터 안에 있고 기대값과 일치하는지 확인
+fn verify_ed25519_instruction(
+    instructions_sysvar_info: &AccountInfo,
+    expected_signer: &Pubkey,
+    expected_message: &[u8],
+) -> std::result::Result<(), ErrorCode> {
+    let current_index = instructions_sysvar::load_current_index_checked(instructions_sysvar_info)
+        .map_err(|_| ErrorCode::InvalidIntentSignature)?;
+    if current_index == 0 {
+        return Err(ErrorCode::InvalidIntentSignature);
+    }
+    
+    let ix = instructions_sysvar::load_instruction_at_checked(
+        (current_index - 1) as usize,
+        instructions_sysvar_info,
+    )
+    .map_err(|_| ErrorCode::InvalidIntentSignature)?;
+    
+    if ix.program_id != ed25519_program::ID || !ix.accounts.is_empty() {
+        return Err(ErrorCode::InvalidIntentSignature);
+    }
+    
+    // 헤더: 서명 개수(u8) + 패딩(u8) + 오프셋 구조체(14바이트)
+    let data = &ix.data;
+    if data.len() < 16 || data[0] != 1 {
+        return Err(ErrorCode::InvalidIntentSignature);
+    }
+    
+    let read_u16 = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+    let signature_instruction_index = read_u16(4);
+    let public_key_offset = read_u16(6) as usize;
+    let public_key_instruction_index = read_u16(8);
+    let message_offset = read_u16(10) as usize;
+    let message_size = read_u16(12) as usize;
+    let message_instruction_index = read_u16(14);
+    
+    // 모든 데이터가 ed25519 명령어 자체에 포함되어야 함
+    if signature_instruction_index != u16::MAX
+        || public_key_instruction_index != u16::MAX
+        || message_instruction_index != u16::MAX
+    {
+        return Err(ErrorCode::InvalidIntentSignature);
+    }
+    
+    let public_key = data
+        .get(public_key_offset..public_key_offset + 32)
+        .ok_or(ErrorCode::InvalidIntentSignature)?;
+    let message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(ErrorCode::InvalidIntentSignature)?;
+    
+    if public_key != expected_signer.as_ref() || message != expected_message {
+        return Err(ErrorCode::InvalidIntentSignature);
+    }
+    
+    Ok(())
+}
+
+/// 논스와 기한을 검증하고 논스를 1 증가 (정확히 한 번 실행 보장)
+fn consume_nonce(
+    key_mapping: &mut KeyMapping,
+    nonce: u64,
+    deadline: i64,
+    current_time: i64,
+) -> std::result::Result<(), ErrorCode> {
+    if current_time > deadline {
+        return Err(ErrorCode::DeadlineExceeded);
+    }
+    if nonce != key_mapping.nonce {
+        return Err(ErrorCode::NonceMismatch);
+    }
+    
+    key_mapping.nonce = key_mapping.nonce.saturating_add(1);
+    Ok(())
+}
+
+/// 보안 정책 계정 로드
+/// 
+/// 수신자 정책과 같은 방식으로 항상 전달받으며 (load_recipient_policy 참고),
+/// 사용량 변경은 호출자가 `exit`로 저장함
+fn load_security_policy<'info>(
+    account_info: &AccountInfo<'info>,
+    program_id: &Pubkey,
+) -> Result<Option<Account<'info, SecurityPolicy>>> {
+    if account_info.owner != program_id || account_info.data_is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Account::<SecurityPolicy>::try_from(account_info)?))
+}
+
+/// 수신자 정책 계정 로드
+/// 
+/// 선택적 계정으로 두면 세션 키 보유자가 계정을 생략해 정책을 우회할 수 있으므로,
+/// 전송 및 릴레이 명령어는 정책 PDA를 검증 없는 계정으로 항상 전달받고 여기서 로드함.
+/// 아직 생성되지 않은 정책 PDA는 제한 없음(None)으로 처리
+fn load_recipient_policy(
+    account_info: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<Option<RecipientPolicy>> {
+    if account_info.owner != program_id || account_info.data_is_empty() {
+        return Ok(None);
+    }
+    let data = account_info.try_borrow_data()?;
+    let recipient_policy = RecipientPolicy::try_deserialize(&mut &data[..])?;
+    Ok(Some(recipient_policy))
+}
+
+/// 수신자 정책 검증
+/// 
+/// * `candidates` - 수신자를 나타내는 주소들 (하나라도 목록에 있으면 등록된 수신자로 간주)
+fn check_recipient(
+    recipient_policy: &RecipientPolicy,
+    candidates: &[Pubkey],
+) -> std::result::Result<(), ErrorCode> {
+    let listed = candidates
+        .iter()
+        .any(|c| recipient_policy.recipients.contains(c));
+    
+    match recipient_policy.mode {
+        RecipientMode::Open => Ok(()),
+        RecipientMode::Allowlist if !listed => Err(ErrorCode::RecipientNotAllowed),
+        RecipientMode::Denylist if listed => Err(ErrorCode::RecipientDenied),
+        _ => Ok(()),
+    }
+}
+
+/// 사용자 계정 프로그램 set_relay_freeze 호출 명령어 생성
+fn set_relay_freeze_instruction(
+    relay_authority: &Pubkey,
+    user_account: &Pubkey,
+    frozen: bool,
+) -> Instruction {
+    // Anchor 명령어 식별자 (sha256("global:set_relay_freeze")의 앞 8바이트) + frozen
+    let mut data = hash(b"global:set_relay_freeze").to_bytes()[..8].to_vec();
+    data.push(frozen as u8);
+    
+    Instruction {
+        program_id: user_account::UserAccount::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*relay_authority, true),
+            AccountMeta::new(*user_account, false),
+        ],
+        data,
+    }
+}
+
+/// 사용자 계정 프로그램의 동결 상태 변경 CPI (relay_authority PDA 서명)
+fn set_user_account_freeze<'info>(
+    relay_authority: &AccountInfo<'info>,
+    user_account: &AccountInfo<'info>,
+    user_account_program: &AccountInfo<'info>,
+    relay_authority_bump: u8,
+    frozen: bool,
+) -> Result<()> {
+    let ix = set_relay_freeze_instruction(relay_authority.key, user_account.key, frozen);
+    
+    invoke_signed(
+        &ix,
+        &[
+            relay_authority.clone(),
+            user_account.clone(),
+            user_account_program.clone(),
+        ],
+        &[&[b"relay_authority", &[relay_authority_bump]]],
+    )?;
+    Ok(())
+}
+
+/// 보안 정책 계정 크기를 현재 내용에 맞게 재할당
+/// 
+/// 늘어나는 경우 렌트 부족분을 `payer`가 지불하고, `shrink`가 참이면 줄어든 만큼의 렌트를 `payer`에게 환급함
+fn resize_security_policy<'info>(
+    security_policy: &Account<'info, SecurityPolicy>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    shrink: bool,
+) -> Result<()> {
+    let account_info = security_policy.to_account_info();
+    let required_len = 8 + security_policy.try_to_vec()?.len();
+    let current_len = account_info.data_len();
+    if required_len == current_len || (required_len < current_len && !shrink) {
+        return Ok(());
+    }
+    
+    let required_lamports = Rent::get()?.minimum_balance(required_len);
+    let current_lamports = account_info.lamports();
+    if required_lamports > current_lamports {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.clone(),
+                    to: account_info.clone(),
+                },
+            ),
+            required_lamports - current_lamports,
+        )?;
+    } else if shrink && current_lamports > required_lamports {
+        let refund = current_lamports - required_lamports;
+        **account_info.try_borrow_mut_lamports()? -= refund;
+        **payer.try_borrow_mut_lamports()? += refund;
+    }
+    
+    account_info.realloc(required_len, false)?;
+    msg!("보안 정책 계정 크기 조정: {} -> {} 바이트", current_len, required_len);
+    Ok(())
+}
+
+/// 이전 버전 수수료 정책 계정 데이터를 현재 구조로 변환 (새 필드는 기본값)
+fn migrated_fee_policy(data: &[u8]) -> std::result::Result<FeePolicy, ErrorCode> {
+    if data.len() != LEGACY_FEE_POLICY_SPACE || data[..8] != FeePolicy::discriminator() {
+        return Err(ErrorCode::NotLegacyAccount);
+    }
+    let legacy = LegacyFeePolicy::try_from_slice(&data[8..])
+        .map_err(|_| ErrorCode::NotLegacyAccount)?;
+    
+    Ok(FeePolicy {
+        fee_collector: legacy.fee_collector,
+        sol_fee_bps: legacy.sol_fee_bps,
+        token_fee_bps: legacy.token_fee_bps,
+        min_fee_amount: legacy.min_fee_amount,
+        authority: legacy.authority,
+        max_fee_amount: 0,
+        fee_mode: FeeMode::Deduct,
+        fee_recipients: Vec::new(),
+        remainder_index: 0,
+        sol_fee_tiers: Vec::new(),
+        token_fee_tiers: Vec::new(),
+    })
+}
+
+/// 이전 버전 토큰별 수수료 정책 계정 데이터를 현재 구조로 변환 (구간 없음)
+fn migrated_token_fee_policy(data: &[u8]) -> std::result::Result<TokenFeePolicy, ErrorCode> {
+    if data.len() != LEGACY_TOKEN_FEE_POLICY_SPACE || data[..8] != TokenFeePolicy::discriminator() {
+        return Err(ErrorCode::NotLegacyAccount);
+    }
+    let legacy = LegacyTokenFeePolicy::try_from_slice(&data[8..])
+        .map_err(|_| ErrorCode::NotLegacyAccount)?;
+    
+    Ok(TokenFeePolicy {
+        token_mint: legacy.token_mint,
+        fee_bps: legacy.fee_bps,
+        fee_tiers: Vec::new(),
+    })
+}
+
+/// 이전 버전 계정을 새 크기로 재할당하고 현재 구조로 다시 기록
+/// 
+/// 렌트 부족분은 `payer`가 지불함
+fn migrate_legacy_account<'info, T: AccountSerialize>(
+    account_info: &AccountInfo<'info>,
+    account: &T,
+    space: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let required_lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = account_info.lamports();
+    if required_lamports > current_lamports {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.clone(),
+                    to: account_info.clone(),
+                },
+            ),
+            required_lamports - current_lamports,
+        )?;
+    }
+    
+    account_info.realloc(space, true)?;
+    let mut data = account_info.try_borrow_mut_data()?;
+    account.try_serialize(&mut &mut data[..])?;
+    Ok(())
+}
+
+/// 경과 시간만큼 롤링 윈도우 사용량 회복
+/// 
+/// 사용량은 `USAGE_SCALE` 배율로 저장되며, 초당 `한도 / 윈도우 길이`만큼 감소함
+fn refresh_usage(security_policy: &mut SecurityPolicy, current_time: i64) {
+    let limits = &security_policy.limits;
+    let elapsed = current_time.saturating_sub(security_policy.last_update).max(0) as u128;
+    let window = limits.window_seconds.max(1) as u128;
+    
+    let tx_recovered = (limits.max_tx_per_window as u128)
+        .saturating_mul(USAGE_SCALE)
+        .saturating_mul(elapsed)
+        / window;
+    let amount_recovered = (limits.max_amount_per_window as u128)
+        .saturating_mul(USAGE_SCALE)
+        .saturating_mul(elapsed)
+        / window;
+    
+    security_policy.tx_usage = security_policy.tx_usage.saturating_sub(tx_recovered);
+    security_policy.amount_usage = security_policy.amount_usage.saturating_sub(amount_recovered);
+    security_policy.last_update = current_time;
+}
+
+/// 경과 시간만큼 토큰별 롤링 윈도우 사용량 회복
+fn refresh_mint_usage(limit: &mut MintLimit, window_seconds: i64, current_time: i64) {
+    let elapsed = current_time.saturating_sub(limit.last_update).max(0) as u128;
+    let window = window_seconds.max(1) as u128;
+    
+    let recovered = (limit.max_amount_per_window as u128)
+        .saturating_mul(USAGE_SCALE)
+        .saturating_mul(elapsed)
+        / window;
+    
+    limit.usage = limit.usage.saturating_sub(recovered);
+    limit.last_update = current_time;
+}
+
+/// 현지 시각(시)이 허용 시간대 안에 있는지 확인
+fn is_hour_allowed(limits: &PolicyLimits, hour: u8) -> bool {
+    let start = limits.allowed_hour_start;
+    let end = limits.allowed_hour_end;
+    
+    if start == end {
+        true
+    } else if start < end {
+        hour >= start && hour < end
+    } else {
+        // 자정을 넘는 구간
+        hour >= start || hour < end
+    }
+}
+
+/// 현재 시각이 허용 시간대 안에 있는지 확인 (UTC 오프셋 적용)
+fn is_within_allowed_hours(limits: &PolicyLimits, current_time: i64) -> bool {
+    let local_time = current_time.saturating_add(limits.utc_offset_minutes as i64 * 60);
+    let hour = (local_time.rem_euclid(86_400) / 3_600) as u8;
+    is_hour_allowed(limits, hour)
+}
+
+/// 윈도우 한도 완화 여부 (한 번에 쓸 수 있는 양 또는 초당 회복량이 늘어나면 완화)
+fn is_rate_loosened(old_cap: u64, old_window: i64, new_cap: u64, new_window: i64) -> bool {
+    new_cap > old_cap
+        || (new_cap as u128) * (old_window.max(1) as u128)
+            > (old_cap as u128) * (new_window.max(1) as u128)
+}
+
+/// 허용 목록 완화 여부 (빈 목록은 전체 허용이므로 제한 해제 또는 새 항목 추가가 완화)
+fn is_allowlist_loosened<T: PartialEq>(old: &[T], new: &[T]) -> bool {
+    !old.is_empty() && (new.is_empty() || new.iter().any(|item| !old.contains(item)))
+}
+
+/// 정책 한도 변경이 한 항목이라도 완화하는지 확인
+fn is_loosening_limits(old: &PolicyLimits, new: &PolicyLimits) -> bool {
+    let hours_loosened = if old.allowed_hour_start == old.allowed_hour_end {
+        // 기존에 시간대 제한 없음
+        false
+    } else if new.allowed_hour_start == new.allowed_hour_end
+        || new.utc_offset_minutes != old.utc_offset_minutes
+    {
+        true
+    } else {
+        (0..24u8).any(|h| is_hour_allowed(new, h) && !is_hour_allowed(old, h))
+    };
+    
+    let functions_loosened = is_allowlist_loosened(&old.allowed_functions, &new.allowed_functions);
+    let calls_loosened = is_allowlist_loosened(&old.allowed_calls, &new.allowed_calls);
+    
+    is_rate_loosened(
+        old.max_tx_per_window as u64,
+        old.window_seconds,
+        new.max_tx_per_window as u64,
+        new.window_seconds,
+    ) || is_rate_loosened(
+        old.max_amount_per_window,
+        old.window_seconds,
+        new.max_amount_per_window,
+        new.window_seconds,
+    ) || new.max_amount_per_tx > old.max_amount_per_tx
+        || new.min_tx_interval < old.min_tx_interval
+        || hours_loosened
+        || functions_loosened
+        || calls_loosened
+        || (new.default_mint_allowed && !old.default_mint_allowed)
+        || new.loosen_delay < old.loosen_delay
+}
+
+/// 토큰별 한도 설정이 제한을 완화하는지 확인
+/// 
+/// 토큰별 한도가 설정된 토큰은 전역 금액 한도 대신 해당 한도만 적용되므로 (applies_global_amount_limits 참고),
+/// 신규 항목은 전역 금액 한도보다 크면 완화로 봄. 첫 항목 추가는 한도 적용 방식이 바뀌므로 항상 완화로 봄
+fn is_mint_limit_loosened(
+    security_policy: &SecurityPolicy,
+    mint: Pubkey,
+    max_amount_per_tx: u64,
+    max_amount_per_window: u64,
+) -> bool {
+    let limits = &security_policy.limits;
+    match security_policy.mint_limits.iter().find(|l| l.mint == mint) {
+        Some(limit) => {
+            max_amount_per_tx > limit.max_amount_per_tx
+                || max_amount_per_window > limit.max_amount_per_window
+        }
+        None => {
+            security_policy.mint_limits.is_empty()
+                || !limits.default_mint_allowed
+                || max_amount_per_tx > limits.max_amount_per_tx
+                || max_amount_per_window > limits.max_amount_per_window
+        }
+    }
+}
+
+/// 수신자 정책 변경이 제한을 완화하는지 확인
+/// 
+/// 허용 목록은 비어 있으면 모든 전송을 막으므로, 보안 정책의 허용 목록과 달리 항목 추가만 완화로 봄
+fn is_recipient_policy_loosened(
+    old: &RecipientPolicy,
+    mode: RecipientMode,
+    recipients: &[Pubkey],
+) -> bool {
+    match (old.mode, mode) {
+        (RecipientMode::Open, _) => false,
+        (_, RecipientMode::Open) => true,
+        (RecipientMode::Allowlist, RecipientMode::Allowlist) => {
+            recipients.iter().any(|r| !old.recipients.contains(r))
+        }
+        (RecipientMode::Allowlist, RecipientMode::Denylist) => true,
+        (RecipientMode::Denylist, RecipientMode::Denylist) => {
+            old.recipients.iter().any(|r| !recipients.contains(r))
+        }
+        (RecipientMode::Denylist, RecipientMode::Allowlist) => {
+            recipients.iter().any(|r| old.recipients.contains(r))
+        }
+    }
+}
+
+/// 보안 정책 변경 예약
+/// 
+/// 기존에 대기 중인 완화 변경은 폐기하고 (강화가 대기 중인 완화에 덮어써지지 않도록),
+/// 완화하는 변경은 `loosen_delay` 이후 적용되도록 등록함. 즉시 적용할 변경은 반환함
+fn queue_policy_change(
+    security_policy: &mut SecurityPolicy,
+    change: PolicyChange,
+    loosening: bool,
+    current_time: i64,
+) -> Option<PolicyChange> {
+    if security_policy.pending_change.take().is_some() {
+        msg!("기존에 대기 중인 정책 변경이 폐기되었습니다");
+    }
+    
+    if loosening && security_policy.limits.loosen_delay > 0 {
+        let executable_at = current_time.saturating_add(security_policy.limits.loosen_delay);
+        security_policy.pending_change = Some(PendingPolicyChange {
+            change,
+            executable_at,
+        });
+        msg!("정책 완화 변경이 등록되었습니다. 적용 가능 시간: {}", executable_at);
+        None
+    } else {
+        Some(change)
+    }
+}
+
+/// 보안 정책 변경 적용 또는 예약
+/// 
+/// 강화하는 변경은 즉시 적용하고, 완화하는 변경은 `loosen_delay` 이후 적용되도록 등록함
+fn stage_policy_change(
+    security_policy: &mut SecurityPolicy,
+    change: PolicyChange,
+    loosening: bool,
+    current_time: i64,
+) -> std::result::Result<(), ErrorCode> {
+    match queue_policy_change(security_policy, change, loosening, current_time) {
+        Some(change) => execute_policy_change(security_policy, change, None, current_time),
+        None => Ok(()),
+    }
+}
+
+/// 보안 정책 변경 적용
+/// 
+/// 기존 사용량은 이전 한도 기준으로 정산 후 유지 (변경으로 한도가 초기화되지 않도록).
+/// 수신자 정책 변경은 `recipient_policy` 계정이 필요함
+fn execute_policy_change(
+    security_policy: &mut SecurityPolicy,
+    change: PolicyChange,
+    recipient_policy: Option<&mut RecipientPolicy>,
+    current_time: i64,
+) -> std::result::Result<(), ErrorCode> {
+    refresh_usage(security_policy, current_time);
+    let window_seconds = security_policy.limits.window_seconds;
+    
+    match change {
+        PolicyChange::Limits(limits) => {
+            for limit in security_policy.mint_limits.iter_mut() {
+                refresh_mint_usage(limit, window_seconds, current_time);
+            }
+            security_policy.limits = limits;
+        }
+        PolicyChange::SetMintLimit {
+            mint,
+            max_amount_per_tx,
+            max_amount_per_window,
+        } => match security_policy.mint_limits.iter_mut().find(|l| l.mint == mint) {
+            Some(limit) => {
+                refresh_mint_usage(limit, window_seconds, current_time);
+                limit.max_amount_per_tx = max_amount_per_tx;
+                limit.max_amount_per_window = max_amount_per_window;
+            }
+            None => {
+                if security_policy.mint_limits.len() >= MAX_MINT_LIMITS {
+                    return Err(ErrorCode::MintLimitCapacityReached);
+                }
+                security_policy.mint_limits.push(MintLimit {
+                    mint,
+                    max_amount_per_tx,
+                    max_amount_per_window,
+                    usage: 0,
+                    last_update: current_time,
+                });
+            }
+        },
+        PolicyChange::RemoveMintLimit { mint } => {
+            let index = security_policy
+                .mint_limits
+                .iter()
+                .position(|l| l.mint == mint)
+                .ok_or(ErrorCode::MintLimitNotFound)?;
+            security_policy.mint_limits.remove(index);
+        }
+        PolicyChange::SetRecipientPolicy { mode, recipients } => {
+            let recipient_policy = recipient_policy.ok_or(ErrorCode::RecipientPolicyMissing)?;
+            recipient_policy.mode = mode;
+            recipient_policy.recipients = recipients;
+        }
+    }
+    Ok(())
+}
+
+/// 전역 금액 한도(`max_amount_per_tx`, `max_amount_per_window`) 적용 여부
+/// 
+/// 토큰별 한도가 설정된 토큰은 소수점 자릿수가 다른 금액이 합산되지 않도록 토큰별 한도만 적용하고,
+/// 토큰별 한도가 없는 토큰과 SOL(네이티브 Mint 또는 Mint 없음)에는 계속 전역 금액 한도를 적용함
+/// (다른 토큰에 한도를 추가해도 한도 없는 토큰이 무제한이 되지 않도록)
+fn applies_global_amount_limits(security_policy: &SecurityPolicy, mint: Option<Pubkey>) -> bool {
+    match mint {
+        Some(mint) if mint != token::spl_token::native_mint::ID => {
+            !security_policy.mint_limits.iter().any(|l| l.mint == mint)
+        }
+        _ => true,
+    }
+}
+
+/// 보안 정책 검증 및 롤링 윈도우 사용량 갱신
+/// 
+/// 모든 트랜잭션에 동일한 검증을 적용하며, 모든 한도를 먼저 검증한 뒤 사용량을 한 번에 갱신함
+/// 
+/// * `program_id` - 호출 대상 프로그램
+/// * `function_id` - 호출 함수 ID
+/// * `mint` - 전송되는 토큰 Mint (토큰 전송이 아니면 None)
+/// * `amount` - 이번 트랜잭션으로 이동하는 금액 (금액이 없는 호출은 0)
+fn apply_security_policy(
+    security_policy: &mut SecurityPolicy,
+    program_id: &Pubkey,
+    function_id: u8,
+    mint: Option<Pubkey>,
+    amount: u64,
+    current_time: i64,
+) -> std::result::Result<(), ErrorCode> {
+    refresh_usage(security_policy, current_time);
+    let limits = &security_policy.limits;
+    
+    // 트랜잭션 간 최소 간격 확인 (연속 인출 방지)
+    if limits.min_tx_interval > 0
+        && current_time.saturating_sub(security_policy.last_tx_at) < limits.min_tx_interval
+    {
+        return Err(ErrorCode::TxCooldownActive);
+    }
+    
+    // 허용 시간대 확인
+    if !is_within_allowed_hours(limits, current_time) {
+        return Err(ErrorCode::OutsideAllowedHours);
+    }
+    
+    // 함수 ID 허용 확인
+    if !limits.allowed_functions.is_empty() && 
+       !limits.allowed_functions.contains(&function_id) {
+        return Err(ErrorCode::FunctionNotAllowed);
+    }
+    
+    // (프로그램, 함수) 호출 허용 확인
+    if !limits.allowed_calls.is_empty()
+        && !limits
+            .allowed_calls
+            .iter()
+            .any(|c| c.program_id == *program_id && c.function_id == function_id)
+    {
+        return Err(ErrorCode::CallNotAllowed);
+    }
+    
+    // 윈도우 내 트랜잭션 수 제한 확인
+    let tx_usage = security_policy.tx_usage.saturating_add(USAGE_SCALE);
+    if tx_usage > (limits.max_tx_per_window as u128).saturating_mul(USAGE_SCALE) {
+        return Err(ErrorCode::WindowTxLimitExceeded);
+    }
+    
+    // 전역 금액 한도 확인 (토큰별 한도가 설정된 토큰은 제외)
+    let mut amount_usage = security_policy.amount_usage;
+    if applies_global_amount_limits(security_policy, mint) {
+        // 트랜잭션당 금액 한도 확인
+        if amount > limits.max_amount_per_tx {
+            return Err(ErrorCode::TxAmountLimitExceeded);
+        }
+        
+        // 윈도우 내 누적 금액 한도 확인
+        amount_usage = amount_usage.saturating_add((amount as u128).saturating_mul(USAGE_SCALE));
+        if amount_usage > (limits.max_amount_per_window as u128).saturating_mul(USAGE_SCALE) {
+            return Err(ErrorCode::WindowAmountLimitExceeded);
+        }
+    }
+    
+    // 토큰별 한도 확인 (한도가 없는 토큰은 기본 허용 설정을 따름)
+    let window_seconds = limits.window_seconds;
+    let default_mint_allowed = limits.default_mint_allowed;
+    let mut mint_update = None;
+    if let Some(mint) = mint {
+        match security_policy.mint_limits.iter().position(|l| l.mint == mint) {
+            Some(index) => {
+                let limit = &mut security_policy.mint_limits[index];
+                refresh_mint_usage(limit, window_seconds, current_time);
+                
+                if amount > limit.max_amount_per_tx {
+                    return Err(ErrorCode::MintTxLimitExceeded);
+                }
+                
+                let usage = limit
+                    .usage
+                    .saturating_add((amount as u128).saturating_mul(USAGE_SCALE));
+                if usage > (limit.max_amount_per_window as u128).saturating_mul(USAGE_SCALE) {
+                    return Err(ErrorCode::MintWindowLimitExceeded);
+                }
+                mint_update = Some((index, usage));
+            }
+            None => {
+                if !default_mint_allowed {
+                    return Err(ErrorCode::MintNotAllowed);
+                }
+            }
+        }
+    }
+    
+    // 사용량 갱신 (한 번만 업데이트)
+    security_policy.last_tx_at = current_time;
+    security_policy.tx_usage = tx_usage;
+    security_policy.amount_usage = amount_usage;
+    if let Some((index, usage)) = mint_update {
+        security_policy.mint_limits[index].usage = usage;
+    }
+    Ok(())
+}
+
+/// 사전 검증 결과 생성 (남은 한도는 이번 요청을 반영하기 전 기준)
+fn policy_check_result(
+    security_policy: &SecurityPolicy,
+    decision: std::result::Result<(), ErrorCode>,
+    mint: Option<Pubkey>,
+    current_time: i64,
+) -> PolicyCheckResult {
+    let mut refreshed = security_policy.clone();
+    refresh_usage(&mut refreshed, current_time);
+    let limits = &refreshed.limits;
+    let window_seconds = limits.window_seconds;
+    
+    let remaining = |cap: u64, usage: u128| -> u64 {
+        ((cap as u128).saturating_mul(USAGE_SCALE).saturating_sub(usage) / USAGE_SCALE) as u64
+    };
+    
+    let remaining_mint_amount = mint.and_then(|mint| {
+        refreshed
+            .mint_limits
+            .iter()
+            .find(|l| l.mint == mint)
+            .map(|limit| {
+                let mut limit = limit.clone();
+                refresh_mint_usage(&mut limit, window_seconds, current_time);
+                remaining(limit.max_amount_per_window, limit.usage)
+            })
+    });
+    
+    PolicyCheckResult {
+        allowed: decision.is_ok(),
+        reason_code: match decision {
+            Ok(()) => 0,
+            Err(error) => error as u32 + anchor_lang::error::ERROR_CODE_OFFSET,
+        },
+        remaining_tx: remaining(limits.max_tx_per_window as u64, refreshed.tx_usage) as u32,
+        remaining_amount: remaining(limits.max_amount_per_window, refreshed.amount_usage),
+        max_amount_per_tx: limits.max_amount_per_tx,
+        remaining_mint_amount,
+        policy_exists: true,
+    }
+}
+
+/// 보안 정책이 없는 사용자의 사전 검증 결과 (모든 호출 허용, 한도 없음)
+fn unrestricted_policy_check_result() -> PolicyCheckResult {
+    PolicyCheckResult {
+        allowed: true,
+        reason_code: 0,
+        remaining_tx: u32::MAX,
+        remaining_amount: u64::MAX,
+        max_amount_per_tx: u64::MAX,
+        remaining_mint_amount: None,
+        policy_exists: false,
+    }
+}
+
+/// 전송 금액에 대한 수수료 계산 (basis points, 최소 수수료 및 최대 수수료 적용)
+/// 
+/// 차감 방식에서 수수료가 전송 금액 이상이면 FeeTooHigh.
+/// 송신자 부담 방식은 수수료를 전송 금액과 별도로 지불하므로 금액과 비교하지 않음
+fn calculate_fee(
+    amount: u64,
+    fee_bps: u16,
+    fee_policy: &FeePolicy,
+) -> std::result::Result<u64, ErrorCode> {
+    let mut fee_amount = (amount as u128 * fee_bps as u128 / 10000) as u64;
+    fee_amount = fee_amount.max(fee_policy.min_fee_amount);
+    if fee_policy.max_fee_amount > 0 {
+        fee_amount = fee_amount.min(fee_policy.max_fee_amount);
+    }
+    
+    if fee_policy.fee_mode == FeeMode::Deduct && fee_amount >= amount {
+        return Err(ErrorCode::FeeTooHigh);
+    }
+    Ok(fee_amount)
+}
+
+/// 수수료를 분배 대상별 가중치 비율로 분할 (대상 순서와 동일한 순서로 반환)
+/// 
+/// 각 대상은 내림한 몫을 받고, 나머지는 remainder_index 위치의 대상이 받음
+fn split_fee(fee_amount: u64, fee_recipients: &[FeeRecipient], remainder_index: u8) -> Vec<u64> {
+    let total_weight: u128 = fee_recipients.iter().map(|r| r.weight as u128).sum();
+    let mut shares: Vec<u64> = fee_recipients
+        .iter()
+        .map(|r| (fee_amount as u128 * r.weight as u128 / total_weight) as u64)
+        .collect();
+    
+    let distributed: u64 = shares.iter().sum();
+    shares[remainder_index as usize] += fee_amount - distributed;
+    shares
+}
+
+/// 페이마스터 후원 한도 유효성 검증 (보전액은 0보다 크고 각 일일 한도 이하)
+fn validate_paymaster_budget(
+    reimbursement_per_call: u64,
+    daily_budget: u64,
+    user_daily_budget: u64,
+) -> std::result::Result<(), ErrorCode> {
+    if reimbursement_per_call == 0
+        || daily_budget < reimbursement_per_call
+        || user_daily_budget < reimbursement_per_call
+    {
+        return Err(ErrorCode::InvalidPaymasterBudget);
+    }
+    Ok(())
+}
+
+/// 페이마스터에서 수수료 지불자에게 릴레이 호출당 보전 금액 지급
+/// 
+/// 페이마스터가 없으면 아무 것도 하지 않으며, 후원자가 등록하지 않은 사용자이거나
+/// 일일 한도나 사용자별 일일 한도를 넘거나 잔액이 부족하면 호출 전체를 거부함
+fn reimburse_fee_payer<'info>(
+    paymaster: Option<&mut Account<'info, Paymaster>>,
+    paymaster_usage: Option<&mut Account<'info, PaymasterUsage>>,
+    fee_payer: &AccountInfo<'info>,
+    user_id: &str,
+    now: i64,
+) -> Result<()> {
+    let (paymaster, paymaster_usage) = match (paymaster, paymaster_usage) {
+        (None, _) => return Ok(()),
+        (Some(paymaster), Some(paymaster_usage)) => (paymaster, paymaster_usage),
+        (Some(_), None) => return Err(ErrorCode::PaymasterUsageMissing.into()),
+    };
+    require_keys_eq!(
+        paymaster_usage.paymaster,
+        paymaster.key(),
+        ErrorCode::PaymasterUsageMissing
+    );
+    
+    // 일 단위 사용량 초기화
+    let today = now.div_euclid(SECONDS_PER_DAY);
+    if paymaster.current_day != today {
+        paymaster.current_day = today;
+        paymaster.spent_today = 0;
+    }
+    if paymaster_usage.current_day != today {
+        paymaster_usage.current_day = today;
+        paymaster_usage.spent_today = 0;
+    }
+    
+    let amount = paymaster.reimbursement_per_call;
+    let spent_today = paymaster.spent_today.saturating_add(amount);
+    let user_spent_today = paymaster_usage.spent_today.saturating_add(amount);
+    require!(spent_today <= paymaster.daily_budget, ErrorCode::PaymasterDailyBudgetExceeded);
+    require!(
+        user_spent_today <= paymaster.user_daily_budget,
+        ErrorCode::PaymasterUserBudgetExceeded
+    );
+    
+    // 렌트 면제 최소 잔액을 남기고 지급
+    let paymaster_info = paymaster.to_account_info();
+    let min_balance = Rent::get()?.minimum_balance(paymaster_info.data_len());
+    require!(
+        paymaster_info.lamports().saturating_sub(min_balance) >= amount,
+        ErrorCode::PaymasterInsufficientFunds
+    );
+    
+    **paymaster_info.try_borrow_mut_lamports()? -= amount;
+    **fee_payer.try_borrow_mut_lamports()? += amount;
+    
+    paymaster.spent_today = spent_today;
+    paymaster.total_reimbursed = paymaster.total_reimbursed.saturating_add(amount);
+    paymaster_usage.spent_today = user_spent_today;
+    
+    msg!("페이마스터 후원: 수수료 지불자 {}에게 {} lamports 보전 (사용자 ID: {})",
+        fee_payer.key(), amount, user_id);
+    Ok(())
+}
+
+/// 수수료 구간 유효성 검증 (최대 MAX_FEE_TIERS개, 최소 거래량 오름차순, 수수료율 최대 20%)
+fn validate_fee_tiers(tiers: &[FeeTier]) -> std::result::Result<(), ErrorCode> {
+    if tiers.len() > MAX_FEE_TIERS {
+        return Err(ErrorCode::InvalidFeeTiers);
+    }
+    if tiers.iter().any(|tier| tier.fee_bps > 2000) {
+        return Err(ErrorCode::FeeTooHigh);
+    }
+    if tiers.windows(2).any(|pair| pair[0].min_volume >= pair[1].min_volume) {
+        return Err(ErrorCode::InvalidFeeTiers);
+    }
+    Ok(())
+}
+
+/// 30일 거래량에 해당하는 수수료율 선택
+/// 
+/// 최소 거래량을 충족하는 가장 높은 구간의 수수료율을 사용하고, 해당 구간이 없으면 기본 수수료율 사용
+fn tiered_fee_bps(base_fee_bps: u16, tiers: &[FeeTier], volume: u64) -> u16 {
+    tiers
+        .iter()
+        .rev()
+        .find(|tier| volume >= tier.min_volume)
+        .map(|tier| tier.fee_bps)
+        .unwrap_or(base_fee_bps)
+}
+
+/// SOL 전송 수수료율 선택 (SOL 수수료율과 SOL 거래량 구간 사용)
+fn sol_fee_bps(fee_policy: &FeePolicy, volume: u64) -> u16 {
+    tiered_fee_bps(fee_policy.sol_fee_bps, &fee_policy.sol_fee_tiers, volume)
+}
+
+/// 토큰 전송 수수료율 선택
+/// 
+/// 해당 Mint의 토큰별 수수료 정책이 있으면 그 정책을, 없으면 기본 토큰 수수료율과 구간을 사용
+fn token_fee_bps(fee_policy: &FeePolicy, token_fee_policy: Option<&TokenFeePolicy>, volume: u64) -> u16 {
+    match token_fee_policy {
+        Some(token_fee_policy) => {
+            tiered_fee_bps(token_fee_policy.fee_bps, &token_fee_policy.fee_tiers, volume)
+        }
+        None => tiered_fee_bps(fee_policy.token_fee_bps, &fee_policy.token_fee_tiers, volume),
+    }
+}
+
+/// 거래량 추적기를 현재 날짜 기준으로 갱신하고 최근 30일 거래량 반환
+/// 
+/// 새로 생성된 추적기는 사용자 ID와 Mint를 기록하며, 집계 기간이 지난 일별 거래량은 0으로 초기화
+fn refresh_volume_tracker(
+    tracker: &mut VolumeTracker,
+    user_id: &str,
+    mint: Pubkey,
+    now: i64,
+) -> u64 {
+    let today = now.div_euclid(SECONDS_PER_DAY);
+    
+    if tracker.user_id.is_empty() {
+        tracker.user_id = user_id.to_string();
+        tracker.mint = mint;
+        tracker.last_day = today;
+    }
+    
+    let elapsed_days = today.saturating_sub(tracker.last_day);
+    if elapsed_days >= VOLUME_WINDOW_DAYS as i64 {
+        tracker.daily_volumes = [0; VOLUME_WINDOW_DAYS];
+    } else {
+        for day in (tracker.last_day + 1)..=today {
+            tracker.daily_volumes[day.rem_euclid(VOLUME_WINDOW_DAYS as i64) as usize] = 0;
+        }
+    }
+    if elapsed_days > 0 {
+        tracker.last_day = today;
+    }
+    
+    tracker
+        .daily_volumes
+        .iter()
+        .fold(0u64, |total, volume| total.saturating_add(*volume))
+}
+
+/// 오늘 날짜의 거래량에 전송 금액 추가 (refresh_volume_tracker 이후 호출)
+fn record_volume(tracker: &mut VolumeTracker, amount: u64, now: i64) {
+    let index = now.div_euclid(SECONDS_PER_DAY).rem_euclid(VOLUME_WINDOW_DAYS as i64) as usize;
+    tracker.daily_volumes[index] = tracker.daily_volumes[index].saturating_add(amount);
+}
+
+/// 세션 권한 범위 검증
+/// 
+/// * `program_id` - 호출 대상 프로그램
+/// * `function_id` - 호출 함수 ID
+/// * `amount` - 이번 호출로 이동하는 금액
+fn check_session_scope(
+    key_mapping: &KeyMapping,
+    program_id: &Pubkey,
+    function_id: u8,
+    amount: u64,
+) -> std::result::Result<(), ErrorCode> {
+    let scope = &key_mapping.scope;
+    
+    if !scope.allowed_programs.is_empty() && !scope.allowed_programs.contains(program_id) {
+        return Err(ErrorCode::ProgramNotInScope);
+    }
+    
+    if !scope.allowed_functions.is_empty() && !scope.allowed_functions.contains(&function_id) {
+        return Err(ErrorCode::FunctionNotInScope);
+    }
+    
+    if scope.max_uses > 0 && key_mapping.use_count >= scope.max_uses {
+        return Err(ErrorCode::SessionUseLimitExceeded);
+    }
+    
+    if scope.spend_limit > 0 && key_mapping.spent_amount.saturating_add(amount) > scope.spend_limit {
+        return Err(ErrorCode::SessionSpendLimitExceeded);
+    }
+    
+    Ok(())
+}
+
+/// 세션 사용 횟수, 누적 지출 금액 및 사용 내역 기록
+/// 
+/// 사용 횟수(`use_count`)와 누적 지출 금액은 세션 권한 범위 검증 대상인 주 임시 키 사용만 집계하고,
+/// 사용 내역(마지막 사용 시간 및 함수 ID, 토큰별 누적 이동 금액)은 서명 키와 무관하게 항상 기록함.
+/// 토큰별 누적 금액은 최근 사용 순으로 유지하며, 가득 찬 상태에서 새 토큰이 들어오면
+/// 가장 오래 사용되지 않은 토큰의 기록을 제거함 (전송이 실패하지 않도록)
+fn record_session_use(
+    key_mapping: &mut KeyMapping,
+    is_main_key: bool,
+    function_id: u8,
+    mint: Option<Pubkey>,
+    amount: u64,
+    now: i64,
+) {
+    if is_main_key {
+        key_mapping.use_count = key_mapping.use_count.saturating_add(1);
+        key_mapping.spent_amount = key_mapping.spent_amount.saturating_add(amount);
+    }
+    
+    let usage = &mut key_mapping.usage;
+    usage.last_used_at = now;
+    usage.last_function_id = function_id;
+    
+    if let Some(mint) = mint.filter(|_| amount > 0) {
+        let mut entry = match usage.mint_totals.iter().position(|m| m.mint == mint) {
+            Some(index) => usage.mint_totals.remove(index),
+            None => {
+                if usage.mint_totals.len() >= MAX_SESSION_MINTS {
+                    usage.mint_totals.remove(0);
+                }
+                SessionMintTotal { mint, total_amount: 0 }
+            }
+        };
+        entry.total_amount = entry.total_amount.saturating_add(amount);
+        usage.mint_totals.push(entry);
+    }
+}
+
+/// 서명자 역할에 따른 철회 사유 결정 (권한이 없으면 None)
+fn revoke_reason(
+    authority: &Pubkey,
+    key_mapping: &KeyMapping,
+    user_authority: &UserAuthority,
+    relay_config: Option<&RelayConfig>,
+) -> Option<RevokeReason> {
+    if *authority == key_mapping.temp_key {
+        Some(RevokeReason::SelfRevoke)
+    } else if *authority == key_mapping.backup_key {
+        Some(RevokeReason::BackupKey)
+    } else if *authority == user_authority.owner {
+        Some(RevokeReason::Owner)
+    } else {
+        match relay_config {
+            Some(config) if *authority == config.admin => Some(RevokeReason::AdminEmergency),
+            _ => None,
+        }
+    }
+}
+
+/// 서명자가 세션의 백업 키 또는 사용자 소유자인지 확인
+fn is_backup_or_owner(
+    authority: &Pubkey,
+    key_mapping: &KeyMapping,
+    user_authority: &UserAuthority,
+) -> bool {
+    *authority == key_mapping.backup_key || *authority == user_authority.owner
+}
+
+/// 대기 중인 정책 변경 취소 권한 확인
+/// 
+/// 사용자 소유자는 항상 취소할 수 있고, 백업 키는 철회되지 않은 키 매핑을 통해서만 취소할 수 있음
+fn check_policy_cancel_authority(
+    authority: &Pubkey,
+    key_mapping: &KeyMapping,
+    user_authority: &UserAuthority,
+) -> std::result::Result<(), ErrorCode> {
+    if *authority != user_authority.owner && key_mapping.revoked {
+        return Err(ErrorCode::RevokedKey);
+    }
+    if !is_backup_or_owner(authority, key_mapping, user_authority) {
+        return Err(ErrorCode::Unauthorized);
+    }
+    Ok(())
+}
+
+/// 백업 키 복구 취소 권한 확인
+/// 
+/// 철회되지 않고 만료되지 않은 키 매핑의 현재 백업 키만 취소할 수 있음
+fn check_recovery_cancel_authority(
+    signer: &Pubkey,
+    key_mapping: &KeyMapping,
+    current_time: i64,
+) -> std::result::Result<(), ErrorCode> {
+    if key_mapping.revoked {
+        return Err(ErrorCode::RevokedKey);
+    }
+    if current_time >= key_mapping.expires_at {
+        return Err(ErrorCode::SessionExpired);
+    }
+    if *signer != key_mapping.backup_key {
+        return Err(ErrorCode::Unauthorized);
+    }
+    Ok(())
+}
+
+/// 세션 일괄 철회 권한 확인
+/// 
+/// 사용자 소유자는 항상 철회할 수 있고, 백업 키는 모든 활성 세션의 백업 키인 경우에만 철회할 수 있음
+/// (활성 세션이 없으면 소유자만 가능)
+fn check_revoke_all_authority(
+    authority: &Pubkey,
+    user_authority: &UserAuthority,
+    backup_keys: &[Pubkey],
+) -> std::result::Result<(), ErrorCode> {
+    if *authority == user_authority.owner {
+        return Ok(());
+    }
+    if backup_keys.is_empty() || backup_keys.iter().any(|k| k != authority) {
+        return Err(ErrorCode::Unauthorized);
+    }
+    Ok(())
+}
+
+/// 긴급 동결 권한 확인
+/// 
+/// 사용자 소유자, 가디언 또는 레지스트리에 등록된 세션의 백업 키만 동결할 수 있음
+/// (활성 세션이 없어도 소유자와 가디언은 동결 가능)
+fn is_emergency_freeze_authority(
+    authority: &Pubkey,
+    user_authority: &UserAuthority,
+    guardian_set: Option<&GuardianSet>,
+    backup_keys: &[Pubkey],
+) -> bool {
+    *authority == user_authority.owner
+        || guardian_set.is_some_and(|g| g.guardians.contains(authority))
+        || backup_keys.contains(authority)
+}
+
+/// 키 매핑이 철회되었거나 만료되어 닫을 수 있는지 확인
+fn is_key_mapping_closable(key_mapping: &KeyMapping, current_time: i64) -> bool {
+    key_mapping.revoked || current_time >= key_mapping.expires_at
+}
+
+/// 세션 레지스트리의 모든 활성 세션에 대한 KeyMapping 계정 로드
+/// 
+/// 전달된 계정이 활성 세션과 정확히 일치하는지(누락, 중복, 다른 사용자 계정 없음) 검증
+fn load_active_key_mappings<'info>(
+    remaining_accounts: &[AccountInfo<'info>],
+    session_registry: &SessionRegistry,
+    current_time: i64,
+    program_id: &Pubkey,
+) -> Result<Vec<Account<'info, KeyMapping>>> {
+    load_key_mappings(
+        remaining_accounts,
+        session_registry,
+        |s| s.expires_at > current_time,
+        program_id,
+    )
+}
+
+/// 세션 레지스트리에 등록된 모든 세션(만료된 세션 포함)에 대한 KeyMapping 계정 로드
+/// 
+/// 전달된 계정이 레지스트리의 세션과 정확히 일치하는지 검증
+fn load_registered_key_mappings<'info>(
+    remaining_accounts: &[AccountInfo<'info>],
+    session_registry: &SessionRegistry,
+    program_id: &Pubkey,
+) -> Result<Vec<Account<'info, KeyMapping>>> {
+    load_key_mappings(remaining_accounts, session_registry, |_| true, program_id)
+}
+
+/// 세션 레지스트리에서 조건을 만족하는 세션에 대한 KeyMapping 계정 로드
+/// 
+/// 전달된 계정이 대상 세션과 정확히 일치하는지(누락, 중복, 다른 사용자 계정 없음) 검증
+fn load_key_mappings<'info>(
+    remaining_accounts: &[AccountInfo<'info>],
+    session_registry: &SessionRegistry,
+    is_target: impl Fn(&SessionEntry) -> bool,
+    program_id: &Pubkey,
+) -> Result<Vec<Account<'info, KeyMapping>>> {
+    let target_count = session_registry.sessions.iter().filter(|s| is_target(s)).count();
+    require!(
+        remaining_accounts.len() == target_count,
+        ErrorCode::IncompleteSessionList
+    );
+    // 같은 계정을 중복 전달하면 개수 검증을 통과하면서 다른 세션을 누락시킬 수 있음
+    require!(
+        !has_duplicate_accounts(remaining_accounts),
+        ErrorCode::DuplicateSessionAccount
+    );
+    
+    let mut key_mappings: Vec<Account<'info, KeyMapping>> = Vec::with_capacity(target_count);
+    for account_info in remaining_accounts.iter() {
+        let key_mapping: Account<'info, KeyMapping> = Account::try_from(account_info)?;
+        
+        // 레지스트리에 등록된 해당 사용자의 대상 세션인지 확인
+        let (expected_pda, _) = Pubkey::find_program_address(
+            &[b"key_mapping", key_mapping.temp_key.as_ref()],
+            program_id,
+        );
+        require!(
+            account_info.key() == expected_pda
+                && key_mapping.user_id == session_registry.user_id
+                && session_registry
+                    .sessions
+                    .iter()
+                    .any(|s| s.temp_key == key_mapping.temp_key && is_target(s)),
+            ErrorCode::SessionNotFound
+        );
+        
+        key_mappings.push(key_mapping);
+    }
+    
+    Ok(key_mappings)
+}
+
+/// 세션 레지스트리의 활성(만료되지 않은) 세션 수
+fn active_session_count(session_registry: &SessionRegistry, current_time: i64) -> usize {
+    session_registry
+        .sessions
+        .iter()
+        .filter(|s| s.expires_at > current_time)
+        .count()
+}
+
+/// 사용자의 철회되었거나 만료된 KeyMapping 계정 로드
+/// 
+/// 키 매핑 PDA 주소와 사용자 ID를 검증하며, 활성 키 매핑은 거부
+fn load_inactive_key_mapping<'info>(
+    account_info: &AccountInfo<'info>,
+    user_id: &str,
+    current_time: i64,
+    program_id: &Pubkey,
+) -> Result<Account<'info, KeyMapping>> {
+    let key_mapping: Account<'info, KeyMapping> = Account::try_from(account_info)?;
+    
+    let (expected_pda, _) = Pubkey::find_program_address(
+        &[b"key_mapping", key_mapping.temp_key.as_ref()],
+        program_id,
+    );
+    require!(
+        account_info.key() == expected_pda && key_mapping.user_id == user_id,
+        ErrorCode::SessionNotFound
+    );
+    require!(
+        is_key_mapping_closable(&key_mapping, current_time),
+        ErrorCode::SessionStillActive
+    );
+    
+    Ok(key_mapping)
+}
+
+/// 전달된 계정 목록에 같은 주소가 두 번 이상 포함되어 있는지 확인
+fn has_duplicate_accounts(accounts: &[AccountInfo]) -> bool {
+    accounts
+        .iter()
+        .enumerate()
+        .any(|(i, account)| accounts[..i].iter().any(|a| a.key == account.key))
+}
+
+/// 만료된 세션을 레지스트리에서 정리
+fn prune_expired_sessions(session_registry: &mut SessionRegistry, current_time: i64) {
+    session_registry.sessions.retain(|s| s.expires_at > current_time);
+}
+
+/// 세션 레지스트리에서 특정 임시 키 세션 제거
+fn remove_session(session_registry: &mut SessionRegistry, temp_key: &Pubkey) {
+    session_registry.sessions.retain(|s| s.temp_key != *temp_key);
+}
+
+/// 세션별 권한 범위 구조체
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct SessionScope {
+    /// 호출 허용 대상 프로그램 목록 (비어 있으면 제한 없음)
+    pub allowed_programs: Vec<Pubkey>,
+    /// 호출 허용 함수 ID 목록 (비어 있으면 제한 없음)
+    pub allowed_functions: Vec<u8>,
+    /// 세션 누적 지출 한도 (0이면 제한 없음)
+    pub spend_limit: u64,
+    /// 세션 최대 사용 횟수 (0이면 제한 없음)
+    pub max_uses: u32,
+}
+
+/// 세션의 토큰(Mint)별 누적 이동 금액 구조체
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SessionMintTotal {
+    /// 토큰 Mint 주소 (SOL은 네이티브 Mint)
+    pub mint: Pubkey,
+    /// 누적 이동 금액 (토큰 최소 단위)
+    pub total_amount: u64,
+}
+
+/// 세션 사용 내역 구조체 (기기별 활동 추적용, 사용 횟수는 KeyMapping.use_count 사용)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct SessionUsage {
+    /// 토큰(Mint)별 누적 이동 금액 (최근 사용 순, 최대 MAX_SESSION_MINTS개)
+    pub mint_totals: Vec<SessionMintTotal>,
+    /// 마지막 사용 시간 (사용 이력이 없으면 0)
+    pub last_used_at: i64,
+    /// 마지막으로 호출한 함수 ID
+    pub last_function_id: u8,
+}
+
+/// 임시 키 매핑 데이터 구조체
+#[account]
+pub struct KeyMapping {
+    /// 임시 키
+    pub temp_key: Pubkey,
+    /// 백업 키 (영구적)
+    pub backup_key: Pubkey,
+    /// 사용자 ID
+    pub user_id: String,
+    /// 사용자 계정 PDA
+    pub user_account_pda: Pubkey,
+    /// 임시 키 만료 시간
+    pub expires_at: i64,
+    /// 철회 여부
+    pub revoked: bool,
+    /// 생성 시간
+    pub created_at: i64,
+    /// 세션 권한 범위
+    pub scope: SessionScope,
+    /// 세션 누적 지출 금액
+    pub spent_amount: u64,
+    /// 세션 사용 횟수
+    pub use_count: u32,
+    /// 계정 생성 비용 지불자 (계정 종료 시 렌트 반환 대상)
+    pub payer: Pubkey,
+    /// 릴레이 요청 논스 (재전송 방지, 사용 시마다 1 증가)
+    pub nonce: u64,
+    /// 긴급 동결 여부
+    pub frozen: bool,
+    /// 세션 사용 내역
+    pub usage: SessionUsage,
+}
+
+/// 임시 키 철회 사유 코드
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RevokeReason {
+    /// 세션 보유자 본인 철회
+    SelfRevoke = 0,
+    /// 백업 키에 의한 철회
+    BackupKey = 1,
+    /// 사용자 소유자에 의한 철회
+    Owner = 2,
+    /// 프로토콜 관리자 긴급 철회
+    AdminEmergency = 3,
+}
+
+/// 수신자 제한 방식
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RecipientMode {
+    /// 제한 없음
+    Open = 0,
+    /// 목록에 있는 주소로만 전송 허용
+    Allowlist = 1,
+    /// 목록에 있는 주소로의 전송 차단
+    Denylist = 2,
+}
+
+/// 수신자 정책 데이터 구조체
+#[account]
+pub struct RecipientPolicy {
+    /// 사용자 ID
+    pub user_id: String,
+    /// 수신자 제한 방식
+    pub mode: RecipientMode,
+    /// 허용 또는 차단할 주소 목록
+    pub recipients: Vec<Pubkey>,
+}
+
+/// 릴레이 전역 설정 데이터 구조체
+#[account]
+pub struct RelayConfig {
+    /// 프로토콜 관리자
+    pub admin: Pubkey,
+    /// 이전 대기 중인 새 관리자 (2단계 이전)
+    pub pending_admin: Option<Pubkey>,
+    /// 릴레이 전역 일시중지 여부
+    pub paused: bool,
+}
+
+/// 사용자 소유자 권한 데이터 구조체
+#[account]
+pub struct UserAuthority {
+    /// 사용자 ID
+    pub user_id: String,
+    /// 소유자 (메인 지갑 또는 사용자 계정 생성자)
+    pub owner: Pubkey,
+    /// 등록 시간
+    pub created_at: i64,
+}
+
+/// 진행 중인 백업 키 복구 요청 구조체
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PendingRecovery {
+    /// 복구 후 사용할 새 백업 키
+    pub new_backup_key: Pubkey,
+    /// 승인한 가디언 목록
+    pub approvals: Vec<Pubkey>,
+    /// 제안 시간
+    pub proposed_at: i64,
+    /// 실행 가능 시간 (임계값 도달 전에는 0)
+    pub executable_at: i64,
+}
+
+/// 사용자별 가디언 설정 데이터 구조체 (소셜 복구)
+#[account]
+pub struct GuardianSet {
+    /// 사용자 ID
+    pub user_id: String,
+    /// 가디언 목록
+    pub guardians: Vec<Pubkey>,
+    /// 복구 승인 임계값 (M-of-N)
+    pub threshold: u8,
+    /// 승인 완료 후 복구 실행까지의 대기 시간 (초)
+    pub recovery_delay: i64,
+    /// 진행 중인 복구 요청
+    pub pending_recovery: Option<PendingRecovery>,
+}
+
+/// 세션 레지스트리 항목 구조체
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SessionEntry {
+    /// 세션 임시 키
+    pub temp_key: Pubkey,
+    /// 세션 만료 시간
+    pub expires_at: i64,
+    /// 세션 생성 시간
+    pub created_at: i64,
+}
+
+/// 사용자별 세션 레지스트리 데이터 구조체 (활성 세션 목록)
+#[account]
+pub struct SessionRegistry {
+    /// 사용자 ID
+    pub user_id: String,
+    /// 활성 세션 목록 (기기별 임시 키)
+    pub sessions: Vec<SessionEntry>,
+    /// 긴급 동결 여부 (동결 중에는 세션 등록, 연장, 교체 불가)
+    pub frozen: bool,
+    /// 동결 해제 가능 시간 (소유자가 해제를 요청한 경우)
+    pub unfreeze_at: Option<i64>,
+}
+
+/// 수수료 정책 데이터 구조체
+#[account]
+pub struct FeePolicy {
+    /// 수수료 수금자
+    pub fee_collector: Pubkey,
+    /// SOL 전송에 대한 수수료율 (basis points, 10000 = 100%)
+    pub sol_fee_bps: u16,
+    /// 토큰 전송에 대한 수수료율 (basis points, 10000 = 100%)
+    pub token_fee_bps: u16,
+    /// 최소 수수료 금액 (lamports)
+    pub min_fee_amount: u64,
+    /// 수수료 정책 관리자
+    pub authority: Pubkey,
+    /// 최대 수수료 금액 (0이면 제한 없음)
+    pub max_fee_amount: u64,
+    /// 수수료 부담 방식
+    pub fee_mode: FeeMode,
+    /// 수수료 분배 대상 목록 (비어 있으면 fee_collector가 전체 수령)
+    pub fee_recipients: Vec<FeeRecipient>,
+    /// 분배 후 나머지를 받을 대상의 목록 내 위치
+    pub remainder_index: u8,
+    /// SOL 전송의 30일 거래량 구간별 수수료율 (최소 거래량 오름차순)
+    pub sol_fee_tiers: Vec<FeeTier>,
+    /// 토큰 전송의 30일 거래량 구간별 수수료율 (최소 거래량 오름차순)
+    pub token_fee_tiers: Vec<FeeTier>,
+}
+
+/// 수수료 분배 대상 구조체
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct FeeRecipient {
+    /// 수령 지갑 (토큰 수수료는 이 지갑 소유의 토큰 계정으로 전송)
+    pub recipient: Pubkey,
+    /// 분배 가중치 (전체 가중치 합 대비 비율로 분배)
+    pub weight: u16,
+}
+
+/// 수수료 부담 방식
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum FeeMode {
+    /// 전송 금액에서 수수료를 차감 (수신자는 금액 - 수수료 수령)
+    Deduct = 0,
+    /// 송신자가 금액 + 수수료를 지불 (수신자는 요청 금액을 그대로 수령)
+    SenderPays = 1,
+}
+
+/// 페이마스터 데이터 구조체 (릴레이 비용 후원 금고, 계정 잔액이 곧 후원 자금)
+#[account]
+pub struct Paymaster {
+    /// 후원자 (페이마스터를 생성하고 관리하는 dApp)
+    pub sponsor: Pubkey,
+    /// 릴레이 호출당 수수료 지불자 보전 금액 (lamports)
+    pub reimbursement_per_call: u64,
+    /// 전체 사용자 대상 일일 후원 한도 (lamports)
+    pub daily_budget: u64,
+    /// 사용자별 일일 후원 한도 (lamports)
+    pub user_daily_budget: u64,
+    /// 사용량 집계 중인 일 번호 (Unix timestamp / 86400)
+    pub current_day: i64,
+    /// 당일 후원 금액 (lamports)
+    pub spent_today: u64,
+    /// 누적 후원 금액 (lamports)
+    pub total_reimbursed: u64,
+}
+
+/// 페이마스터 사용자별 후원 사용량 구조체
+#[account]
+pub struct PaymasterUsage {
+    /// 페이마스터 주소
+    pub paymaster: Pubkey,
+    /// 사용자 ID
+    pub user_id: String,
+    /// 사용량 집계 중인 일 번호 (Unix timestamp / 86400)
+    pub current_day: i64,
+    /// 당일 후원 금액 (lamports)
+    pub spent_today: u64,
+}
+
+/// 거래량 구간별 수수료율 구조체
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct FeeTier {
+    /// 구간 적용 최소 30일 거래량 (토큰 최소 단위 또는 lamports)
+    pub min_volume: u64,
+    /// 구간 수수료율 (basis points, 10000 = 100%)
+    pub fee_bps: u16,
+}
+
+/// 사용자별 토큰(Mint) 거래량 추적 데이터 구조체 (수수료 구간 산정용)
+#[account]
+pub struct VolumeTracker {
+    /// 사용자 ID
+    pub user_id: String,
+    /// 토큰 Mint 주소 (SOL은 네이티브 Mint)
+    pub mint: Pubkey,
+    /// 일별 거래량 순환 버퍼 (일 번호 % VOLUME_WINDOW_DAYS 위치에 기록)
+    pub daily_volumes: [u64; 30],
+    /// 마지막으로 갱신된 일 번호 (Unix timestamp / 86400)
+    pub last_day: i64,
+}
+
+/// 이전 버전 수수료 정책 데이터 구조체 (마이그레이션용)
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyFeePolicy {
+    pub fee_collector: Pubkey,
+    pub sol_fee_bps: u16,
+    pub token_fee_bps: u16,
+    pub min_fee_amount: u64,
+    pub authority: Pubkey,
+}
+
+/// 토큰별 수수료 정책 데이터 구조체
+#[account]
+pub struct TokenFeePolicy {
+    /// 토큰 Mint 주소
+    pub token_mint: Pubkey,
+    /// 해당 토큰에 대한 수수료율 (basis points, 10000 = 100%)
+    pub fee_bps: u16,
+    /// 30일 거래량 구간별 수수료율 (최소 거래량 오름차순)
+    pub fee_tiers: Vec<FeeTier>,
+}
+
+/// 이전 버전 토큰별 수수료 정책 데이터 구조체 (마이그레이션용)
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyTokenFeePolicy {
+    pub token_mint: Pubkey,
+    pub fee_bps: u16,
+}
+
+/// 토큰(Mint)별 전송 한도 항목 구조체
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MintLimit {
+    /// 토큰 Mint 주소
+    pub mint: Pubkey,
+    /// 트랜잭션당 최대 금액 (토큰 최소 단위)
+    pub max_amount_per_tx: u64,
+    /// 윈도우당 최대 금액 (토큰 최소 단위)
+    pub max_amount_per_window: u64,
+    /// 롤링 윈도우 사용량 (USAGE_SCALE 배율)
+    pub usage: u128,
+    /// 사용량이 마지막으로 정산된 시간
+    pub last_update: i64,
+}
+
+/// 보안 정책 사전 검증 결과 (check_policy 반환 데이터)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PolicyCheckResult {
+    /// 허용 여부
+    pub allowed: bool,
+    /// 거부 사유 코드 (실제 호출 시 반환될 오류 코드, 허용이면 0)
+    pub reason_code: u32,
+    /// 현재 윈도우에서 남은 트랜잭션 수
+    pub remaining_tx: u32,
+    /// 현재 윈도우에서 남은 금액
+    pub remaining_amount: u64,
+    /// 트랜잭션당 최대 금액
+    pub max_amount_per_tx: u64,
+    /// 해당 토큰의 윈도우 내 남은 금액 (토큰별 한도가 없으면 None)
+    pub remaining_mint_amount: Option<u64>,
+    /// 보안 정책 존재 여부 (없으면 한도 없이 모든 호출 허용)
+    pub policy_exists: bool,
+}
+
+/// 호출 허용 항목 구조체
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct AllowedCall {
+    /// 라우팅 대상 프로그램 ID
+    pub program_id: Pubkey,
+    /// 호출 함수 ID
+    pub function_id: u8,
+}
+
+/// 보안 정책 한도 설정 구조체 (완화 변경 대기열에 그대로 저장됨)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PolicyLimits {
+    /// 한도 윈도우 길이 (초)
+    pub window_seconds: i64,
+    /// 윈도우당 최대 트랜잭션 수
+    pub max_tx_per_window: u32,
+    /// 트랜잭션당 최대 금액
+    pub max_amount_per_tx: u64,
+    /// 윈도우당 최대 금액
+    pub max_amount_per_window: u64,
+    /// 트랜잭션 간 최소 간격 (초, 0이면 제한 없음)
+    pub min_tx_interval: i64,
+    /// 허용 시간대 시작 시각 (현지 시각, 포함)
+    pub allowed_hour_start: u8,
+    /// 허용 시간대 종료 시각 (현지 시각, 미포함, 시작과 같으면 제한 없음)
+    pub allowed_hour_end: u8,
+    /// 현지 시각 계산용 UTC 오프셋 (분)
+    pub utc_offset_minutes: i16,
+    /// 토큰별 한도가 없는 토큰의 전송 허용 여부
+    pub default_mint_allowed: bool,
+    /// 완화 변경 적용까지의 대기 시간 (초)
+    pub loosen_delay: i64,
+    /// 허용된 함수 ID 목록 (비어 있으면 전체 허용)
+    pub allowed_functions: Vec<u8>,
+    /// 허용된 (프로그램 ID, 함수 ID) 목록 (비어 있으면 전체 허용)
+    pub allowed_calls: Vec<AllowedCall>,
+}
+
+impl PolicyLimits {
+    /// 허용 목록이 비어 있을 때의 직렬화 크기 (목록 항목은 재할당으로 확장)
+    pub const BASE_SPACE: usize = 8 + // window_seconds: i64
+        4 +  // max_tx_per_window: u32
+        8 +  // max_amount_per_tx: u64
+        8 +  // max_amount_per_window: u64
+        8 +  // min_tx_interval: i64
+        1 +  // allowed_hour_start: u8
+        1 +  // allowed_hour_end: u8
+        2 +  // utc_offset_minutes: i16
+        1 +  // default_mint_allowed: bool
+        8 +  // loosen_delay: i64
+        4 +  // allowed_functions: Vec<u8>
+        4;   // allowed_calls: Vec<AllowedCall>
+}
+
+/// 보안 정책 변경 종류
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum PolicyChange {
+    /// 정책 한도 전체 교체
+    Limits(PolicyLimits),
+    /// 토큰별 한도 추가 또는 갱신
+    SetMintLimit {
+        mint: Pubkey,
+        max_amount_per_tx: u64,
+        max_amount_per_window: u64,
+    },
+    /// 토큰별 한도 삭제
+    RemoveMintLimit { mint: Pubkey },
+    /// 수신자 정책 교체
+    SetRecipientPolicy {
+        mode: RecipientMode,
+        recipients: Vec<Pubkey>,
+    },
+}
+
+/// 대기 중인 정책 완화 변경 구조체
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PendingPolicyChange {
+    /// 적용할 변경 내용
+    pub change: PolicyChange,
+    /// 적용 가능 시간
+    pub executable_at: i64,
+}
+
+/// 보안 정책 데이터 구조체
 #[account]
-pub struct FeePolicy {
-    /// 수수료 수금자
-    pub fee_collector: Pubkey,
-    /// SOL 전송에 대한 수수료율 (basis points, 10000 = 100%)
-    pub sol_fee_bps: u16,
-    /// 토큰 전송에 대한 수수료율 (basis points, 10000 = 100%)
-    pub token_fee_bps: u16,
-    /// 최소 수수료 금액 (lamports)
-    pub min_fee_amount: u64,
-    /// 수수료 정책 관리자
-    pub authority: Pubkey,
+pub struct SecurityPolicy {
+    /// 사용자 ID
+    pub user_id: String,
+    /// 현재 적용 중인 한도 설정
+    pub limits: PolicyLimits,
+    /// 롤링 윈도우 트랜잭션 사용량 (USAGE_SCALE 배율)
+    pub tx_usage: u128,
+    /// 롤링 윈도우 누적 금액 사용량 (USAGE_SCALE 배율)
+    pub amount_usage: u128,
+    /// 사용량이 마지막으로 정산된 시간
+    pub last_update: i64,
+    /// 마지막 트랜잭션 시간
+    pub last_tx_at: i64,
+    /// 토큰(Mint)별 전송 한도 목록
+    pub mint_limits: Vec<MintLimit>,
+    /// 대기 중인 정책 완화 변경
+    pub pending_change: Option<PendingPolicyChange>,
+}
+
+/// 수신자 정책 설정 명령어 계정 구조체
+#[derive(Accounts)]
+#[instruction(user_id: String)]
+pub struct SetRecipientPolicy<'info> {
+    /// 사용자 소유자 (지불자)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    /// 사용자 소유자 권한
+    #[account(
+        seeds = [b"user_authority", user_id.as_bytes()],
+        bump,
+        constraint = user_authority.owner == owner.key() @ ErrorCode::Unauthorized
+    )]
+    pub user_authority: Account<'info, UserAuthority>,
+    
+    /// 수신자 정책 데이터
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [b"recipient_policy", user_id.as_bytes()],
+        bump,
+        space = 8 + // 디스크리미네이터
+               4 + user_id.len() + // user_id: String
+               1 + // mode: RecipientMode
+               4 + MAX_RECIPIENTS * 32 // recipients: Vec<Pubkey>
+    )]
+    pub recipient_policy: Account<'info, RecipientPolicy>,
+    
+    /// 보안 정책 PDA (완화 변경 대기 시간 적용용)
+    /// CHECK: seeds로 주소만 검증하고 내용은 함수에서 로드 (load_security_policy 참고)
+    #[account(
+        mut,
+        seeds = [b"security_policy", user_id.as_bytes()],
+        bump,
+    )]
+    pub security_policy: UncheckedAccount<'info>,
+    
+    /// 시스템 프로그램
+    pub system_program: Program<'info, System>,
+}
+
+/// 보안 정책 항목 변경 명령어 계정 구조체 (사용자 소유자 전용)
+#[derive(Accounts)]
+pub struct UpdateSecurityPolicy<'info> {
+    /// 사용자 소유자 (계정 크기 증가 시 렌트 지불자)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// 사용자 소유자 권한
+    #[account(
+        seeds = [b"user_authority", security_policy.user_id.as_bytes()],
+        bump,
+        constraint = user_authority.owner == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub user_authority: Account<'info, UserAuthority>,
+    
+    /// 보안 정책 데이터
+    #[account(
+        mut,
+        seeds = [b"security_policy", security_policy.user_id.as_bytes()],
+        bump,
+    )]
+    pub security_policy: Account<'info, SecurityPolicy>,
+    
+    /// 시스템 프로그램
+    pub system_program: Program<'info, System>,
+}
+
+/// 보안 정책 사전 검증 명령어 계정 구조체
+#[derive(Accounts)]
+#[instruction(user_id: String)]
+pub struct CheckPolicy<'info> {
+    /// 보안 정책 PDA (읽기 전용, 아직 생성되지 않았을 수 있음)
+    /// CHECK: seeds로 주소만 검증하고 내용은 함수에서 로드 (load_security_policy 참고)
+    #[account(
+        seeds = [b"security_policy", user_id.as_bytes()],
+        bump,
+    )]
+    pub security_policy: UncheckedAccount<'info>,
+}
+
+/// 대기 중인 정책 변경 적용 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct ApplyPolicyChange<'info> {
+    /// 호출자 (계정 크기 증가 시 렌트 지불자)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// 보안 정책 데이터
+    #[account(
+        mut,
+        seeds = [b"security_policy", security_policy.user_id.as_bytes()],
+        bump,
+    )]
+    pub security_policy: Account<'info, SecurityPolicy>,
+    
+    /// 수신자 정책 데이터 (대기 중인 변경이 수신자 정책 변경인 경우 필수)
+    #[account(
+        mut,
+        seeds = [b"recipient_policy", security_policy.user_id.as_bytes()],
+        bump,
+    )]
+    pub recipient_policy: Option<Account<'info, RecipientPolicy>>,
+    
+    /// 시스템 프로그램
+    pub system_program: Program<'info, System>,
+}
+
+/// 대기 중인 정책 변경 취소 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct CancelPolicyChange<'info> {
+    /// 백업 키 또는 사용자 소유자 서명자
+    pub authority: Signer<'info>,
+    
+    /// 백업 키가 등록된 임시 키 매핑 데이터
+    #[account(
+        seeds = [b"key_mapping", key_mapping.temp_key.as_ref()],
+        bump,
+        constraint = key_mapping.user_id == security_policy.user_id @ ErrorCode::Unauthorized
+    )]
+    pub key_mapping: Account<'info, KeyMapping>,
+    
+    /// 사용자 소유자 권한
+    #[account(
+        seeds = [b"user_authority", security_policy.user_id.as_bytes()],
+        bump,
+    )]
+    pub user_authority: Account<'info, UserAuthority>,
+    
+    /// 보안 정책 데이터
+    #[account(
+        mut,
+        seeds = [b"security_policy", security_policy.user_id.as_bytes()],
+        bump,
+    )]
+    pub security_policy: Account<'info, SecurityPolicy>,
+}
+
+/// 릴레이 설정 초기화 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct InitializeRelayConfig<'info> {
+    /// 프로그램 업그레이드 권한자 (지불자)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// 릴레이 설정 데이터
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"relay_config"],
+        bump,
+        space = 8 + // 디스크리미네이터
+               32 + // admin: Pubkey
+               1 + 32 + // pending_admin: Option<Pubkey>
+               1    // paused: bool
+    )]
+    pub relay_config: Account<'info, RelayConfig>,
+    
+    /// AA 릴레이 프로그램
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::AaRelay>,
+    
+    /// AA 릴레이 프로그램 데이터 (업그레이드 권한자 확인용)
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    
+    /// 시스템 프로그램
+    pub system_program: Program<'info, System>,
+}
+
+/// 릴레이 관리자 전용 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct RelayAdminOperation<'info> {
+    /// 릴레이 관리자
+    pub admin: Signer<'info>,
+    
+    /// 릴레이 설정 데이터
+    #[account(
+        mut,
+        seeds = [b"relay_config"],
+        bump,
+        constraint = relay_config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub relay_config: Account<'info, RelayConfig>,
+}
+
+/// 관리자 이전 수락 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    /// 제안된 새 관리자
+    pub new_admin: Signer<'info>,
+    
+    /// 릴레이 설정 데이터
+    #[account(
+        mut,
+        seeds = [b"relay_config"],
+        bump,
+    )]
+    pub relay_config: Account<'info, RelayConfig>,
+}
+
+/// 수수료 정책 설정 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct SetFeePolicy<'info> {
+    /// 권한 있는 사용자 (릴레이 관리자)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// 릴레이 설정 (관리자 확인용)
+    #[account(
+        seeds = [b"relay_config"],
+        bump,
+        constraint = relay_config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub relay_config: Account<'info, RelayConfig>,
+    
+    /// 수수료 수금자 계정
+    /// CHECK: 수수료를 받을 계정
+    pub fee_collector: AccountInfo<'info>,
+    
+    /// 수수료 정책 데이터
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [b"fee_policy"],
+        bump,
+        space = FEE_POLICY_SPACE
+    )]
+    pub fee_policy: Account<'info, FeePolicy>,
+    
+    /// 시스템 프로그램
+    pub system_program: Program<'info, System>,
+}
+
+/// 수수료 정책 변경 명령어 계정 구조체 (기존 수수료 정책 대상)
+#[derive(Accounts)]
+pub struct UpdateFeePolicy<'info> {
+    /// 권한 있는 사용자 (릴레이 관리자)
+    pub authority: Signer<'info>,
+    
+    /// 릴레이 설정 (관리자 확인용)
+    #[account(
+        seeds = [b"relay_config"],
+        bump,
+        constraint = relay_config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub relay_config: Account<'info, RelayConfig>,
+    
+    /// 수수료 정책 데이터
+    #[account(
+        mut,
+        seeds = [b"fee_policy"],
+        bump,
+    )]
+    pub fee_policy: Account<'info, FeePolicy>,
+}
+
+/// 수수료 정책 마이그레이션 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct MigrateFeePolicy<'info> {
+    /// 권한 있는 사용자 (릴레이 관리자, 추가 렌트 지불)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// 릴레이 설정 (관리자 확인용)
+    #[account(
+        seeds = [b"relay_config"],
+        bump,
+        constraint = relay_config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub relay_config: Account<'info, RelayConfig>,
+    
+    /// 수수료 정책 데이터 (이전 버전 구조)
+    /// CHECK: 현재 구조로 역직렬화되지 않으므로 함수에서 식별자와 크기를 직접 검증
+    #[account(
+        mut,
+        seeds = [b"fee_policy"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub fee_policy: UncheckedAccount<'info>,
+    
+    /// 시스템 프로그램
+    pub system_program: Program<'info, System>,
+}
+
+/// 페이마스터 생성 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct CreatePaymaster<'info> {
+    /// 후원자 (생성 비용 지불)
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+    
+    /// 페이마스터 데이터
+    #[account(
+        init,
+        payer = sponsor,
+        seeds = [b"paymaster", sponsor.key().as_ref()],
+        bump,
+        space = 8 + // 디스크리미네이터
+               32 + // sponsor: Pubkey
+               8 +  // reimbursement_per_call: u64
+               8 +  // daily_budget: u64
+               8 +  // user_daily_budget: u64
+               8 +  // current_day: i64
+               8 +  // spent_today: u64
+               8    // total_reimbursed: u64
+    )]
+    pub paymaster: Account<'info, Paymaster>,
+    
+    /// 시스템 프로그램
+    pub system_program: Program<'info, System>,
+}
+
+/// 페이마스터 변경 및 출금 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct UpdatePaymaster<'info> {
+    /// 후원자 (출금 금액 수령)
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+    
+    /// 페이마스터 데이터
+    #[account(
+        mut,
+        seeds = [b"paymaster", sponsor.key().as_ref()],
+        bump,
+        has_one = sponsor @ ErrorCode::Unauthorized,
+    )]
+    pub paymaster: Account<'info, Paymaster>,
+}
+
+/// 페이마스터 후원 대상 사용자 등록 명령어 계정 구조체
+#[derive(Accounts)]
+#[instruction(user_id: String)]
+pub struct AddPaymasterUser<'info> {
+    /// 후원자 (등록 계정 생성 비용 지불)
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+    
+    /// 페이마스터 데이터
+    #[account(
+        seeds = [b"paymaster", sponsor.key().as_ref()],
+        bump,
+        has_one = sponsor @ ErrorCode::Unauthorized,
+    )]
+    pub paymaster: Account<'info, Paymaster>,
+    
+    /// 사용자별 후원 사용량 (후원 대상 등록 정보)
+    #[account(
+        init,
+        payer = sponsor,
+        seeds = [b"paymaster_usage", paymaster.key().as_ref(), user_id.as_bytes()],
+        bump,
+        space = 8 + // 디스크리미네이터
+               32 + // paymaster: Pubkey
+               4 + user_id.len() + // user_id: String
+               8 +  // current_day: i64
+               8    // spent_today: u64
+    )]
+    pub paymaster_usage: Account<'info, PaymasterUsage>,
+    
+    /// 시스템 프로그램
+    pub system_program: Program<'info, System>,
+}
+
+/// 페이마스터 후원 대상 사용자 해제 명령어 계정 구조체
+#[derive(Accounts)]
+#[instruction(user_id: String)]
+pub struct RemovePaymasterUser<'info> {
+    /// 후원자 (렌트 반환 대상)
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+    
+    /// 페이마스터 데이터
+    #[account(
+        seeds = [b"paymaster", sponsor.key().as_ref()],
+        bump,
+        has_one = sponsor @ ErrorCode::Unauthorized,
+    )]
+    pub paymaster: Account<'info, Paymaster>,
+    
+    /// 사용자별 후원 사용량 (후원 대상 등록 정보)
+    #[account(
+        mut,
+        close = sponsor,
+        seeds = [b"paymaster_usage", paymaster.key().as_ref(), user_id.as_bytes()],
+        bump,
+    )]
+    pub paymaster_usage: Account<'info, PaymasterUsage>,
+}
+
+/// 페이마스터 예치 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct FundPaymaster<'info> {
+    /// 예치자
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    
+    /// 페이마스터 데이터
+    #[account(
+        mut,
+        seeds = [b"paymaster", paymaster.sponsor.as_ref()],
+        bump,
+    )]
+    pub paymaster: Account<'info, Paymaster>,
+    
+    /// 시스템 프로그램
+    pub system_program: Program<'info, System>,
+}
+
+/// 토큰별 수수료 정책 설정 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct SetTokenFeePolicy<'info> {
+    /// 권한 있는 사용자 (릴레이 관리자)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// 릴레이 설정 (관리자 확인용)
+    #[account(
+        seeds = [b"relay_config"],
+        bump,
+        constraint = relay_config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub relay_config: Account<'info, RelayConfig>,
+    
+    /// 토큰 Mint 주소
+    /// CHECK: 토큰 민트는 단순 식별자로만 사용됩니다
+    pub token_mint: AccountInfo<'info>,
+    
+    /// 토큰별 수수료 정책 데이터
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [b"token_fee_policy", token_mint.key().as_ref()],
+        bump,
+        space = TOKEN_FEE_POLICY_SPACE
+    )]
+    pub token_fee_policy: Account<'info, TokenFeePolicy>,
+    
+    /// 시스템 프로그램
+    pub system_program: Program<'info, System>,
+}
+
+/// 토큰별 수수료 정책 마이그레이션 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct MigrateTokenFeePolicy<'info> {
+    /// 권한 있는 사용자 (릴레이 관리자, 추가 렌트 지불)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// 릴레이 설정 (관리자 확인용)
+    #[account(
+        seeds = [b"relay_config"],
+        bump,
+        constraint = relay_config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub relay_config: Account<'info, RelayConfig>,
+    
+    /// 토큰 Mint 주소
+    /// CHECK: 토큰 민트는 단순 식별자로만 사용됩니다
+    pub token_mint: AccountInfo<'info>,
+    
+    /// 토큰별 수수료 정책 데이터 (이전 버전 구조)
+    /// CHECK: 현재 구조로 역직렬화되지 않으므로 함수에서 식별자와 크기를 직접 검증
+    #[account(
+        mut,
+        seeds = [b"token_fee_policy", token_mint.key().as_ref()],
+        bump,
+        owner = crate::ID,
+    )]
+    pub token_fee_policy: UncheckedAccount<'info>,
+    
+    /// 시스템 프로그램
+    pub system_program: Program<'info, System>,
+}
+
+/// 보안 정책 설정 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct SetSecurityPolicy<'info> {
+    /// 권한 있는 사용자 (사용자 소유자)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// 임시 키 매핑 데이터
+    pub key_mapping: Account<'info, KeyMapping>,
+    
+    /// 사용자 소유자 권한
+    #[account(
+        seeds = [b"user_authority", key_mapping.user_id.as_bytes()],
+        bump,
+        constraint = user_authority.owner == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub user_authority: Account<'info, UserAuthority>,
+    
+    /// 보안 정책 데이터
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [b"security_policy", key_mapping.user_id.as_bytes()],
+        bump,
+        // 최초 생성 시 빈 목록 기준 크기로 할당하고 이후 내용에 맞게 재할당함.
+        // 이미 재할당된 기존 계정은 현재 크기를 그대로 사용
+        space = security_policy.to_account_info().data_len().max(
+            8 + // 디스크리미네이터
+            4 + key_mapping.user_id.len() + // user_id: String
+            PolicyLimits::BASE_SPACE + // limits: PolicyLimits
+            16 + // tx_usage: u128
+            16 + // amount_usage: u128
+            8 + // last_update: i64
+            8 + // last_tx_at: i64
+            4 + // mint_limits: Vec<MintLimit>
+            1   // pending_change: Option 태그 (None)
+        )
+    )]
+    pub security_policy: Account<'info, SecurityPolicy>,
+    
+    /// 시스템 프로그램
+    pub system_program: Program<'info, System>,
+}
+
+/// 사용자 소유자 등록 명령어 계정 구조체
+#[derive(Accounts)]
+#[instruction(user_id: String)]
+pub struct RegisterOwner<'info> {
+    /// 사용자 계정 생성자 (지불자)
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    /// 소유자로 지정할 계정 (메인 지갑 또는 생성자)
+    /// CHECK: 소유자는 단순 식별자로 저장되며 이후 명령어에서 서명 검증
+    pub owner: AccountInfo<'info>,
+    
+    /// 사용자 계정 PDA
+    /// CHECK: 사용자 계정 프로그램 소유 여부와 생성자를 직접 검증
+    #[account(
+        seeds = [b"user_account", user_id.as_bytes()],
+        bump,
+        seeds::program = user_account::UserAccount::id(),
+    )]
+    pub user_account: AccountInfo<'info>,
+    
+    /// 사용자 소유자 권한
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"user_authority", user_id.as_bytes()],
+        bump,
+        space = 8 + // 디스크리미네이터
+               4 + user_id.len() + // user_id: String
+               32 + // owner: Pubkey
+               8    // created_at: i64
+    )]
+    pub user_authority: Account<'info, UserAuthority>,
+    
+    /// 시스템 프로그램
+    pub system_program: Program<'info, System>,
+}
+
+/// 임시 키 및 백업 키 등록 명령어 계정 구조체
+#[derive(Accounts)]
+#[instruction(user_id: String, expires_at: i64, scope: SessionScope)]
+pub struct RegisterTempKeys<'info> {
+    /// 트랜잭션 지불자 (관리자)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// 사용자 소유자 (등록 승인 서명자)
+    pub owner: Signer<'info>,
+    
+    /// 사용자 소유자 권한
+    #[account(
+        seeds = [b"user_authority", user_id.as_bytes()],
+        bump,
+        constraint = user_authority.owner == owner.key() @ ErrorCode::Unauthorized
+    )]
+    pub user_authority: Account<'info, UserAuthority>,
+    
+    /// 임시 키 계정
+    /// CHECK: 임시 키는 서명 검증이 필요 없음, 단순 식별자로 사용
+    pub temp_key: AccountInfo<'info>,
+    
+    /// 백업 키 계정
+    /// CHECK: 백업 키는 서명 검증이 필요 없음, 단순 식별자로 사용
+    pub backup_key: AccountInfo<'info>,
+    
+    /// 임시 키 매핑 데이터
+    #[account(
+        init,
+        payer = payer,
+        seeds = [
+            b"key_mapping",
+            temp_key.key().as_ref(),
+        ],
+        bump,
+        // 정확한 공간 계산
+        space = 8 + // 디스크리미네이터
+               32 + // temp_key: Pubkey
+               32 + // backup_key: Pubkey
+               4 + user_id.len() + // user_id: String (길이 헤더 + 데이터)
+               32 + // user_account_pda: Pubkey
+               8 +  // expires_at: i64
+               1 +  // revoked: bool
+               8 +  // created_at: i64
+               4 + 32 * scope.allowed_programs.len() + // scope.allowed_programs: Vec<Pubkey>
+               4 + scope.allowed_functions.len() + // scope.allowed_functions: Vec<u8>
+               8 +  // scope.spend_limit: u64
+               4 +  // scope.max_uses: u32
+               8 +  // spent_amount: u64
+               4 +  // use_count: u32
+               32 + // payer: Pubkey
+               8 +  // nonce: u64
+               1 +  // frozen: bool
+               4 + MAX_SESSION_MINTS * (32 + 8) + // usage.mint_totals: Vec<SessionMintTotal>
+               8 +  // usage.last_used_at: i64
+               1    // usage.last_function_id: u8
+    )]
+    pub key_mapping: Account<'info, KeyMapping>,
+    
+    /// 세션 레지스트리
+    #[account(
+        init_if_needed,
+        payer = payer,
+        seeds = [b"session_registry", user_id.as_bytes()],
+        bump,
+        space = 8 + // 디스크리미네이터
+               4 + user_id.len() + // user_id: String
+               4 + MAX_SESSIONS_PER_USER * (
+                   32 + // temp_key: Pubkey
+                   8 +  // expires_at: i64
+                   8    // created_at: i64
+               ) +
+               1 + // frozen: bool
+               1 + 8 // unfreeze_at: Option<i64>
+    )]
+    pub session_registry: Account<'info, SessionRegistry>,
+    
+    /// 시스템 프로그램
+    pub system_program: Program<'info, System>,
+}
+
+/// 임시 키 철회 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct RevokeTempKey<'info> {
+    /// 철회 권한자 (임시 키, 백업 키, 사용자 소유자 또는 프로토콜 관리자)
+    pub authority: Signer<'info>,
+    
+    /// 임시 키 매핑 데이터
+    #[account(
+        mut,
+        seeds = [
+            b"key_mapping",
+            key_mapping.temp_key.as_ref(),
+        ],
+        bump,
+    )]
+    pub key_mapping: Account<'info, KeyMapping>,
+    
+    /// 세션 레지스트리
+    #[account(
+        mut,
+        seeds = [b"session_registry", key_mapping.user_id.as_bytes()],
+        bump,
+    )]
+    pub session_registry: Account<'info, SessionRegistry>,
+    
+    /// 사용자 소유자 권한
+    #[account(
+        seeds = [b"user_authority", key_mapping.user_id.as_bytes()],
+        bump,
+    )]
+    pub user_authority: Account<'info, UserAuthority>,
+    
+    /// 릴레이 설정 (선택적, 관리자 긴급 철회 시 필요)
+    #[account(
+        seeds = [b"relay_config"],
+        bump,
+    )]
+    pub relay_config: Option<Account<'info, RelayConfig>>,
+}
+
+/// 세션 일괄 철회 명령어 계정 구조체
+#[derive(Accounts)]
+#[instruction(user_id: String)]
+pub struct RevokeAllSessions<'info> {
+    /// 철회 권한자 (사용자 소유자 또는 백업 키 소유자)
+    pub authority: Signer<'info>,
+    
+    /// 사용자 소유자 권한
+    #[account(
+        seeds = [b"user_authority", user_id.as_bytes()],
+        bump,
+    )]
+    pub user_authority: Account<'info, UserAuthority>,
+    
+    /// 세션 레지스트리
+    #[account(
+        mut,
+        seeds = [b"session_registry", user_id.as_bytes()],
+        bump,
+    )]
+    pub session_registry: Account<'info, SessionRegistry>,
+}
+
+/// 백업 키 변경 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct ChangeBackupKey<'info> {
+    /// 현재 백업 키 서명자 (현재 백업 키의 소유자)
+    pub current_backup_key_signer: Signer<'info>,
+    
+    /// 사용자 소유자 (변경 승인 서명자)
+    pub owner: Signer<'info>,
+    
+    /// 새로운 백업 키 계정
+    /// CHECK: 백업 키는 서명 검증이 필요 없음, 단순 식별자로 사용
+    pub new_backup_key: AccountInfo<'info>,
+    
+    /// 임시 키 매핑 데이터
+    #[account(
+        mut,
+        seeds = [
+            b"key_mapping",
+            key_mapping.temp_key.as_ref(),
+        ],
+        bump,
+    )]
+    pub key_mapping: Account<'info, KeyMapping>,
+    
+    /// 사용자 소유자 권한
+    #[account(
+        seeds = [b"user_authority", key_mapping.user_id.as_bytes()],
+        bump,
+        constraint = user_authority.owner == owner.key() @ ErrorCode::Unauthorized
+    )]
+    pub user_authority: Account<'info, UserAuthority>,
+}
+
+/// 가디언 설정 명령어 계정 구조체
+#[derive(Accounts)]
+#[instruction(user_id: String)]
+pub struct SetGuardians<'info> {
+    /// 사용자 소유자 (지불자)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    /// 사용자 소유자 권한
+    #[account(
+        seeds = [b"user_authority", user_id.as_bytes()],
+        bump,
+        constraint = user_authority.owner == owner.key() @ ErrorCode::Unauthorized
+    )]
+    pub user_authority: Account<'info, UserAuthority>,
+    
+    /// 가디언 설정 데이터
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [b"guardian_set", user_id.as_bytes()],
+        bump,
+        space = 8 + // 디스크리미네이터
+               4 + user_id.len() + // user_id: String
+               4 + MAX_GUARDIANS * 32 + // guardians: Vec<Pubkey>
+               1 +  // threshold: u8
+               8 +  // recovery_delay: i64
+               1 +  // pending_recovery: Option 태그
+               32 + // pending_recovery.new_backup_key: Pubkey
+               4 + MAX_GUARDIANS * 32 + // pending_recovery.approvals: Vec<Pubkey>
+               8 +  // pending_recovery.proposed_at: i64
+               8    // pending_recovery.executable_at: i64
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+    
+    /// 시스템 프로그램
+    pub system_program: Program<'info, System>,
+}
+
+/// 가디언 전용 명령어 계정 구조체 (복구 제안 및 승인)
+#[derive(Accounts)]
+pub struct GuardianOperation<'info> {
+    /// 가디언 서명자
+    pub guardian: Signer<'info>,
+    
+    /// 가디언 설정 데이터
+    #[account(
+        mut,
+        seeds = [b"guardian_set", guardian_set.user_id.as_bytes()],
+        bump,
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+}
+
+/// 백업 키 복구 실행 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct ExecuteRecovery<'info> {
+    /// 가디언 설정 데이터
+    #[account(
+        mut,
+        seeds = [b"guardian_set", guardian_set.user_id.as_bytes()],
+        bump,
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+    
+    /// 세션 레지스트리
+    #[account(
+        seeds = [b"session_registry", guardian_set.user_id.as_bytes()],
+        bump,
+    )]
+    pub session_registry: Account<'info, SessionRegistry>,
 }
 
-/// 토큰별 수수료 정책 데이터 구조체
-#[account]
-pub struct TokenFeePolicy {
+/// 긴급 동결 명령어 계정 구조체
+#[derive(Accounts)]
+#[instruction(user_id: String)]
+pub struct EmergencyFreeze<'info> {
+    /// 사용자 소유자, 가디언 또는 백업 키 서명자
+    pub authority: Signer<'info>,
+    
+    /// 사용자 소유자 권한
+    #[account(
+        seeds = [b"user_authority", user_id.as_bytes()],
+        bump,
+    )]
+    pub user_authority: Account<'info, UserAuthority>,
+    
+    /// 세션 레지스트리
+    #[account(
+        mut,
+        seeds = [b"session_registry", user_id.as_bytes()],
+        bump,
+    )]
+    pub session_registry: Account<'info, SessionRegistry>,
+    
+    /// 가디언 설정 (가디언이 요청하는 경우)
+    #[account(
+        seeds = [b"guardian_set", user_id.as_bytes()],
+        bump,
+    )]
+    pub guardian_set: Option<Account<'info, GuardianSet>>,
+    
+    /// 사용자 계정 CPI 서명용 릴레이 권한 PDA
+    /// CHECK: 데이터가 없는 서명 전용 PDA
+    #[account(
+        seeds = [b"relay_authority"],
+        bump,
+    )]
+    pub relay_authority: AccountInfo<'info>,
+    
+    /// 사용자 계정 (UserAccountData)
+    /// CHECK: 사용자 계정 프로그램 PDA 주소로 검증하며, 데이터는 사용자 계정 프로그램에서 갱신
+    #[account(
+        mut,
+        seeds = [b"user_account", user_id.as_bytes()],
+        bump,
+        seeds::program = user_account_program.key(),
+    )]
+    pub user_account: AccountInfo<'info>,
+    
+    /// 사용자 계정 프로그램
+    /// CHECK: 주소 제약으로 사용자 계정 프로그램임을 검증
+    #[account(address = user_account::UserAccount::id(), executable)]
+    pub user_account_program: AccountInfo<'info>,
+}
+
+/// 동결 해제 요청 명령어 계정 구조체
+#[derive(Accounts)]
+#[instruction(user_id: String)]
+pub struct RequestUnfreeze<'info> {
+    /// 사용자 소유자
+    pub owner: Signer<'info>,
+    
+    /// 사용자 소유자 권한
+    #[account(
+        seeds = [b"user_authority", user_id.as_bytes()],
+        bump,
+        constraint = user_authority.owner == owner.key() @ ErrorCode::Unauthorized
+    )]
+    pub user_authority: Account<'info, UserAuthority>,
+    
+    /// 세션 레지스트리
+    #[account(
+        mut,
+        seeds = [b"session_registry", user_id.as_bytes()],
+        bump,
+    )]
+    pub session_registry: Account<'info, SessionRegistry>,
+}
+
+/// 동결 해제 명령어 계정 구조체
+#[derive(Accounts)]
+#[instruction(user_id: String)]
+pub struct Unfreeze<'info> {
+    /// 사용자 소유자
+    pub owner: Signer<'info>,
+    
+    /// 사용자 소유자 권한
+    #[account(
+        seeds = [b"user_authority", user_id.as_bytes()],
+        bump,
+        constraint = user_authority.owner == owner.key() @ ErrorCode::Unauthorized
+    )]
+    pub user_authority: Account<'info, UserAuthority>,
+    
+    /// 세션 레지스트리
+    #[account(
+        mut,
+        seeds = [b"session_registry", user_id.as_bytes()],
+        bump,
+    )]
+    pub session_registry: Account<'info, SessionRegistry>,
+    
+    /// 사용자 계정 CPI 서명용 릴레이 권한 PDA
+    /// CHECK: 데이터가 없는 서명 전용 PDA
+    #[account(
+        seeds = [b"relay_authority"],
+        bump,
+    )]
+    pub relay_authority: AccountInfo<'info>,
+    
+    /// 사용자 계정 (UserAccountData)
+    /// CHECK: 사용자 계정 프로그램 PDA 주소로 검증하며, 데이터는 사용자 계정 프로그램에서 갱신
+    #[account(
+        mut,
+        seeds = [b"user_account", user_id.as_bytes()],
+        bump,
+        seeds::program = user_account_program.key(),
+    )]
+    pub user_account: AccountInfo<'info>,
+    
+    /// 사용자 계정 프로그램
+    /// CHECK: 주소 제약으로 사용자 계정 프로그램임을 검증
+    #[account(address = user_account::UserAccount::id(), executable)]
+    pub user_account_program: AccountInfo<'info>,
+}
+
+/// 백업 키 복구 취소 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct CancelRecovery<'info> {
+    /// 현재 백업 키 서명자
+    pub backup_key_signer: Signer<'info>,
+    
+    /// 현재 백업 키가 등록된 임시 키 매핑 데이터
+    #[account(
+        seeds = [b"key_mapping", key_mapping.temp_key.as_ref()],
+        bump,
+        constraint = key_mapping.user_id == guardian_set.user_id @ ErrorCode::Unauthorized
+    )]
+    pub key_mapping: Account<'info, KeyMapping>,
+    
+    /// 가디언 설정 데이터
+    #[account(
+        mut,
+        seeds = [b"guardian_set", guardian_set.user_id.as_bytes()],
+        bump,
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+}
+
+/// 세션 연장 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct ExtendSession<'info> {
+    /// 백업 키 또는 사용자 소유자 서명자
+    pub authority: Signer<'info>,
+    
+    /// 임시 키 매핑 데이터
+    #[account(
+        mut,
+        seeds = [b"key_mapping", key_mapping.temp_key.as_ref()],
+        bump,
+    )]
+    pub key_mapping: Account<'info, KeyMapping>,
+    
+    /// 사용자 소유자 권한
+    #[account(
+        seeds = [b"user_authority", key_mapping.user_id.as_bytes()],
+        bump,
+    )]
+    pub user_authority: Account<'info, UserAuthority>,
+    
+    /// 세션 레지스트리
+    #[account(
+        mut,
+        seeds = [b"session_registry", key_mapping.user_id.as_bytes()],
+        bump,
+    )]
+    pub session_registry: Account<'info, SessionRegistry>,
+}
+
+/// 임시 키 교체 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct RotateTempKey<'info> {
+    /// 새 키 매핑 계정 생성 비용 지불자
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// 백업 키 또는 사용자 소유자 서명자
+    pub authority: Signer<'info>,
+    
+    /// 새 임시 키 계정
+    /// CHECK: 임시 키는 서명 검증이 필요 없음, 단순 식별자로 사용
+    pub new_temp_key: AccountInfo<'info>,
+    
+    /// 기존 임시 키 매핑 데이터 (이전 후 닫힘)
+    #[account(
+        mut,
+        close = original_payer,
+        seeds = [b"key_mapping", key_mapping.temp_key.as_ref()],
+        bump,
+    )]
+    pub key_mapping: Account<'info, KeyMapping>,
+    
+    /// 기존 키 매핑의 원래 지불자 (렌트 반환 대상)
+    /// CHECK: 키 매핑에 기록된 지불자 주소와 일치하는지 검증
+    #[account(mut, address = key_mapping.payer @ ErrorCode::PayerMismatch)]
+    pub original_payer: AccountInfo<'info>,
+    
+    /// 새 임시 키 매핑 데이터
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"key_mapping", new_temp_key.key().as_ref()],
+        bump,
+        space = key_mapping.to_account_info().data_len()
+    )]
+    pub new_key_mapping: Account<'info, KeyMapping>,
+    
+    /// 사용자 소유자 권한
+    #[account(
+        seeds = [b"user_authority", key_mapping.user_id.as_bytes()],
+        bump,
+    )]
+    pub user_authority: Account<'info, UserAuthority>,
+    
+    /// 세션 레지스트리
+    #[account(
+        mut,
+        seeds = [b"session_registry", key_mapping.user_id.as_bytes()],
+        bump,
+    )]
+    pub session_registry: Account<'info, SessionRegistry>,
+    
+    /// 시스템 프로그램
+    pub system_program: Program<'info, System>,
+}
+
+/// 키 매핑 종료 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct CloseKeyMapping<'info> {
+    /// 백업 키, 사용자 소유자 또는 원래 지불자 서명자
+    pub authority: Signer<'info>,
+    
+    /// 임시 키 매핑 데이터 (닫힘)
+    #[account(
+        mut,
+        close = original_payer,
+        seeds = [b"key_mapping", key_mapping.temp_key.as_ref()],
+        bump,
+    )]
+    pub key_mapping: Account<'info, KeyMapping>,
+    
+    /// 키 매핑의 원래 지불자 (렌트 반환 대상)
+    /// CHECK: 키 매핑에 기록된 지불자 주소와 일치하는지 검증
+    #[account(mut, address = key_mapping.payer @ ErrorCode::PayerMismatch)]
+    pub original_payer: AccountInfo<'info>,
+    
+    /// 사용자 소유자 권한
+    #[account(
+        seeds = [b"user_authority", key_mapping.user_id.as_bytes()],
+        bump,
+    )]
+    pub user_authority: Account<'info, UserAuthority>,
+    
+    /// 세션 레지스트리
+    #[account(
+        mut,
+        seeds = [b"session_registry", key_mapping.user_id.as_bytes()],
+        bump,
+    )]
+    pub session_registry: Account<'info, SessionRegistry>,
+}
+
+/// 키 매핑 일괄 정리 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct CloseExpiredKeyMappings<'info> {
+    /// 크랭크 호출자 (권한 제한 없음)
+    pub cranker: Signer<'info>,
+}
+
+/// SPL 토큰 전송 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct TransferSplToken<'info> {
+    /// 수수료 지불자 (관리자 또는 서비스 제공자)
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    
+    /// 임시 키 또는 백업 키 서명자
+    #[account(mut)]
+    pub temp_key: Signer<'info>,
+    
     /// 토큰 Mint 주소
-    pub token_mint: Pubkey,
-    /// 해당 토큰에 대한 수수료율 (basis points, 10000 = 100%)
-    pub fee_bps: u16,
+    /// CHECK: 토큰 민트는 단순 식별자로만 사용됩니다
+    pub mint: AccountInfo<'info>,
+    
+    /// 송신자 토큰 계정
+    #[account(
+        mut,
+        constraint = from.mint == mint.key() @ ErrorCode::MintMismatch
+    )]
+    pub from: Account<'info, TokenAccount>,
+    
+    /// 수신자 토큰 계정
+    #[account(mut)]
+    pub to: Account<'info, TokenAccount>,
+    
+    /// 수수료 수금자 토큰 계정 (fee_policy.fee_collector 소유, 전송 토큰과 같은 Mint)
+    #[account(
+        mut,
+        constraint = fee_collector.owner == fee_policy.fee_collector @ ErrorCode::FeeCollectorMismatch,
+        constraint = fee_collector.mint == mint.key() @ ErrorCode::FeeCollectorMismatch
+    )]
+    pub fee_collector: Account<'info, TokenAccount>,
+    
+    /// 임시 키 매핑 데이터
+    #[account(
+        mut,
+        seeds = [b"key_mapping", temp_key.key().as_ref()],
+        bump
+    )]
+    pub key_mapping: Account<'info, KeyMapping>,
+    
+    /// 수수료 정책
+    #[account(
+        seeds = [b"fee_policy"],
+        bump,
+    )]
+    pub fee_policy: Account<'info, FeePolicy>,
+    
+    /// 토큰별 수수료 정책 (선택적, 없으면 기본 토큰 수수료율 적용)
+    #[account(
+        seeds = [b"token_fee_policy", mint.key().as_ref()],
+        bump,
+    )]
+    pub token_fee_policy: Option<Account<'info, TokenFeePolicy>>,
+    
+    /// 사용자 거래량 추적기 (수수료 구간 산정용, 최초 전송 시 생성)
+    #[account(
+        init_if_needed,
+        payer = fee_payer,
+        seeds = [b"volume_tracker", key_mapping.user_id.as_bytes(), mint.key().as_ref()],
+        bump,
+        space = 8 + // 디스크리미네이터
+               4 + key_mapping.user_id.len() + // user_id: String
+               32 + // mint: Pubkey
+               8 * VOLUME_WINDOW_DAYS + // daily_volumes: [u64; 30]
+               8    // last_day: i64
+    )]
+    pub volume_tracker: Account<'info, VolumeTracker>,
+    
+    /// 보안 정책 PDA
+    /// CHECK: seeds로 주소만 검증하고 내용은 함수에서 로드 (load_security_policy 참고)
+    #[account(
+        mut,
+        seeds = [b"security_policy", key_mapping.user_id.as_bytes()],
+        bump,
+    )]
+    pub security_policy: UncheckedAccount<'info>,
+    
+    /// 수신자 정책 PDA
+    /// CHECK: seeds로 주소만 검증하고 내용은 함수에서 로드 (load_recipient_policy 참고)
+    #[account(
+        seeds = [b"recipient_policy", key_mapping.user_id.as_bytes()],
+        bump,
+    )]
+    pub recipient_policy: UncheckedAccount<'info>,
+    
+    /// 페이마스터 (선택적, 수수료 지불자에게 호출당 비용 보전)
+    #[account(
+        mut,
+        seeds = [b"paymaster", paymaster.sponsor.as_ref()],
+        bump,
+    )]
+    pub paymaster: Option<Account<'info, Paymaster>>,
+    
+    /// 페이마스터 후원 대상 사용자 등록 정보 (페이마스터 사용 시 필수, 후원자가 add_paymaster_user로 생성)
+    #[account(
+        mut,
+        seeds = [
+            b"paymaster_usage",
+            paymaster.as_ref().map(|p| p.key()).unwrap_or_default().as_ref(),
+            key_mapping.user_id.as_bytes(),
+        ],
+        bump,
+    )]
+    pub paymaster_usage: Option<Account<'info, PaymasterUsage>>,
+    
+    /// 릴레이 설정 (전역 일시중지 확인용)
+    #[account(
+        seeds = [b"relay_config"],
+        bump,
+    )]
+    pub relay_config: Account<'info, RelayConfig>,
+    
+    /// 토큰 프로그램
+    pub token_program: Program<'info, Token>,
+    
+    /// 시스템 프로그램 (거래량 추적기 생성용)
+    pub system_program: Program<'info, System>,
+}
+
+/// SOL 전송 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct TransferSol<'info> {
+    /// 수수료 지불자 (관리자 또는 서비스 제공자)
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    
+    /// 임시 키 또는 백업 키 서명자 (전송되는 lamports의 출처)
+    #[account(mut)]
+    pub temp_key: Signer<'info>,
+    
+    /// 수신자 계정
+    /// CHECK: lamports를 받기만 하는 계정
+    #[account(mut)]
+    pub to: UncheckedAccount<'info>,
+    
+    /// 수수료 수금자 계정
+    /// CHECK: fee_policy.fee_collector와 일치하는지 검증
+    #[account(
+        mut,
+        constraint = fee_collector.key() == fee_policy.fee_collector @ ErrorCode::FeeCollectorMismatch
+    )]
+    pub fee_collector: UncheckedAccount<'info>,
+    
+    /// 임시 키 매핑 데이터
+    #[account(
+        mut,
+        seeds = [b"key_mapping", temp_key.key().as_ref()],
+        bump
+    )]
+    pub key_mapping: Account<'info, KeyMapping>,
+    
+    /// 수수료 정책 (SOL 전송은 수수료를 생략할 수 없도록 필수)
+    #[account(
+        seeds = [b"fee_policy"],
+        bump,
+    )]
+    pub fee_policy: Account<'info, FeePolicy>,
+    
+    /// 사용자 거래량 추적기 (수수료 구간 산정용, 최초 전송 시 생성)
+    #[account(
+        init_if_needed,
+        payer = fee_payer,
+        seeds = [b"volume_tracker", key_mapping.user_id.as_bytes(), token::spl_token::native_mint::ID.as_ref()],
+        bump,
+        space = 8 + // 디스크리미네이터
+               4 + key_mapping.user_id.len() + // user_id: String
+               32 + // mint: Pubkey
+               8 * VOLUME_WINDOW_DAYS + // daily_volumes: [u64; 30]
+               8    // last_day: i64
+    )]
+    pub volume_tracker: Account<'info, VolumeTracker>,
+    
+    /// 보안 정책 PDA
+    /// CHECK: seeds로 주소만 검증하고 내용은 함수에서 로드 (load_security_policy 참고)
+    #[account(
+        mut,
+        seeds = [b"security_policy", key_mapping.user_id.as_bytes()],
+        bump,
+    )]
+    pub security_policy: UncheckedAccount<'info>,
+    
+    /// 수신자 정책 PDA
+    /// CHECK: seeds로 주소만 검증하고 내용은 함수에서 로드 (load_recipient_policy 참고)
+    #[account(
+        seeds = [b"recipient_policy", key_mapping.user_id.as_bytes()],
+        bump,
+    )]
+    pub recipient_policy: UncheckedAccount<'info>,
+    
+    /// 페이마스터 (선택적, 수수료 지불자에게 호출당 비용 보전)
+    #[account(
+        mut,
+        seeds = [b"paymaster", paymaster.sponsor.as_ref()],
+        bump,
+    )]
+    pub paymaster: Option<Account<'info, Paymaster>>,
+    
+    /// 페이마스터 후원 대상 사용자 등록 정보 (페이마스터 사용 시 필수, 후원자가 add_paymaster_user로 생성)
+    #[account(
+        mut,
+        seeds = [
+            b"paymaster_usage",
+            paymaster.as_ref().map(|p| p.key()).unwrap_or_default().as_ref(),
+            key_mapping.user_id.as_bytes(),
+        ],
+        bump,
+    )]
+    pub paymaster_usage: Option<Account<'info, PaymasterUsage>>,
+    
+    /// 릴레이 설정 (전역 일시중지 확인용)
+    #[account(
+        seeds = [b"relay_config"],
+        bump,
+    )]
+    pub relay_config: Account<'info, RelayConfig>,
+    
+    /// 시스템 프로그램
+    pub system_program: Program<'info, System>,
 }
 
-/// 보안 정책 데이터 구조체
-#[account]
-pub struct SecurityPolicy {
-    /// 사용자 ID
-    pub user_id: String,
-    /// 일일 최대 트랜잭션 수
-    pub max_tx_per_day: u32,
-    /// 트랜잭션당 최대 금액
-    pub max_amount_per_tx: u64,
-    /// 일일 최대 금액
-    pub max_amount_per_day: u64,
-    /// 현재 일일 트랜잭션 카운트
-    pub daily_tx_count: u32,
-    /// 현재 일일 누적 금액
-    pub daily_amount: u64,
-    /// 마지막 일자 (unix timestamp / 86400)
-    pub last_day: i64,
-    /// 허용된 함수 ID 목록
-    pub allowed_functions: Vec<u8>,
+/// 트랜잭션 릴레이 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct RelayTransaction<'info> {
+    /// 수수료 지불자 (관리자 또는 서비스 제공자)
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    
+    /// 임시 키 또는 백업 키 서명자
+    #[account(mut)]
+    pub temp_key: Signer<'info>,
+    
+    /// AA 릴레이 프로그램 ID
+    /// CHECK: 프로그램 ID는 유저 계정에 저장된 값과 일치하는지 확인하는 용도로만 사용
+    #[account(address = crate::ID)]
+    pub aa_relay_program: AccountInfo<'info>,
+    
+    /// 임시 키 매핑 데이터
+    #[account(
+        mut,
+        seeds = [b"key_mapping", temp_key.key().as_ref()],
+        bump
+    )]
+    pub key_mapping: Account<'info, KeyMapping>,
+    
+    /// 보안 정책 PDA
+    /// CHECK: seeds로 주소만 검증하고 내용은 함수에서 로드 (load_security_policy 참고)
+    #[account(
+        mut,
+        seeds = [b"security_policy", key_mapping.user_id.as_bytes()],
+        bump,
+    )]
+    pub security_policy: UncheckedAccount<'info>,
+    
+    /// 수신자 정책 PDA
+    /// CHECK: seeds로 주소만 검증하고 내용은 함수에서 로드 (load_recipient_policy 참고)
+    #[account(
+        seeds = [b"recipient_policy", key_mapping.user_id.as_bytes()],
+        bump,
+    )]
+    pub recipient_policy: UncheckedAccount<'info>,
+    
+    /// 사용자 계정 PDA (CPI 대상)
+    /// CHECK: 키 매핑에 등록된 주소인지 검증하고 내용은 사용자 계정 프로그램이 검증
+    #[account(mut, address = key_mapping.user_account_pda)]
+    pub user_account: UncheckedAccount<'info>,
+    
+    /// 라우팅 대상 서비스 프로그램
+    /// CHECK: 함수 ID로 결정되는 프로그램과 일치하는지 함수에서 검증 (routed_program 참고)
+    #[account(executable)]
+    pub target_program: AccountInfo<'info>,
+    
+    /// 페이마스터 (선택적, 수수료 지불자에게 호출당 비용 보전)
+    #[account(
+        mut,
+        seeds = [b"paymaster", paymaster.sponsor.as_ref()],
+        bump,
+    )]
+    pub paymaster: Option<Account<'info, Paymaster>>,
+    
+    /// 페이마스터 후원 대상 사용자 등록 정보 (페이마스터 사용 시 필수, 후원자가 add_paymaster_user로 생성)
+    #[account(
+        mut,
+        seeds = [
+            b"paymaster_usage",
+            paymaster.as_ref().map(|p| p.key()).unwrap_or_default().as_ref(),
+            key_mapping.user_id.as_bytes(),
+        ],
+        bump,
+    )]
+    pub paymaster_usage: Option<Account<'info, PaymasterUsage>>,
+    
+    /// 릴레이 설정 (전역 일시중지 확인용)
+    #[account(
+        seeds = [b"relay_config"],
+        bump,
+    )]
+    pub relay_config: Account<'info, RelayConfig>,
+    
+    /// 시스템 프로그램 (수수료 처리용)
+    pub system_program: Program<'info, System>,
 }
 
-/// 수수료 정책 설정 명령어 계정 구조체
+/// 서명된 인텐트 릴레이 명령어 계정 구조체
 #[derive(Accounts)]
-pub struct SetFeePolicy<'info> {
-    /// 권한 있는 사용자 (관리자)
+pub struct RelaySignedIntent<'info> {
+    /// 수수료 지불자 (제3자 릴레이어)
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub fee_payer: Signer<'info>,
+    
+    /// 인텐트에 서명한 임시 키
+    /// CHECK: 서명은 ed25519 프로그램 명령어를 통해 검증
+    pub temp_key: AccountInfo<'info>,
+    
+    /// 임시 키 매핑 데이터
+    #[account(
+        mut,
+        seeds = [b"key_mapping", temp_key.key().as_ref()],
+        bump
+    )]
+    pub key_mapping: Account<'info, KeyMapping>,
+    
+    /// 보안 정책 PDA
+    /// CHECK: seeds로 주소만 검증하고 내용은 함수에서 로드 (load_security_policy 참고)
+    #[account(
+        mut,
+        seeds = [b"security_policy", key_mapping.user_id.as_bytes()],
+        bump,
+    )]
+    pub security_policy: UncheckedAccount<'info>,
+    
+    /// 수신자 정책 PDA
+    /// CHECK: seeds로 주소만 검증하고 내용은 함수에서 로드 (load_recipient_policy 참고)
+    #[account(
+        seeds = [b"recipient_policy", key_mapping.user_id.as_bytes()],
+        bump,
+    )]
+    pub recipient_policy: UncheckedAccount<'info>,
+    
+    /// 라우팅 대상 서비스 프로그램
+    /// CHECK: 함수 ID로 결정되는 프로그램과 일치하는지 함수에서 검증 (routed_program 참고)
+    #[account(executable)]
+    pub target_program: AccountInfo<'info>,
+    
+    /// AA 릴레이 프로그램 (사용자 계정 프로그램에 호출자 프로그램으로 전달)
+    /// CHECK: 주소 제약으로 이 프로그램임을 검증
+    #[account(address = crate::ID)]
+    pub aa_relay_program: AccountInfo<'info>,
+    
+    /// 사용자 계정 PDA (CPI 대상)
+    /// CHECK: 키 매핑에 등록된 주소인지 검증하고 내용은 사용자 계정 프로그램이 검증
+    #[account(mut, address = key_mapping.user_account_pda)]
+    pub user_account: UncheckedAccount<'info>,
+    
+    /// 명령어 시스템 변수 (ed25519 서명 검증 명령어 조회용)
+    /// CHECK: 주소 제약으로 명령어 시스템 변수임을 검증
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+    
+    /// 페이마스터 (선택적, 수수료 지불자에게 호출당 비용 보전)
+    #[account(
+        mut,
+        seeds = [b"paymaster", paymaster.sponsor.as_ref()],
+        bump,
+    )]
+    pub paymaster: Option<Account<'info, Paymaster>>,
+    
+    /// 페이마스터 후원 대상 사용자 등록 정보 (페이마스터 사용 시 필수, 후원자가 add_paymaster_user로 생성)
+    #[account(
+        mut,
+        seeds = [
+            b"paymaster_usage",
+            paymaster.as_ref().map(|p| p.key()).unwrap_or_default().as_ref(),
+            key_mapping.user_id.as_bytes(),
+        ],
+        bump,
+    )]
+    pub paymaster_usage: Option<Account<'info, PaymasterUsage>>,
+    
+    /// 릴레이 설정 (전역 일시중지 확인용)
+    #[account(
+        seeds = [b"relay_config"],
+        bump,
+    )]
+    pub relay_config: Account<'info, RelayConfig>,
+    
+    /// 시스템 프로그램
+    pub system_program: Program<'info, System>,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("권한이 없습니다")]
+    Unauthorized,
+    
+    #[msg("세션이 만료되었습니다")]
+    SessionExpired,
+    
+    #[msg("세션이 철회되었습니다")]
+    SessionRevoked,
+    
+    #[msg("이미 철회된 키입니다")]
+    AlreadyRevoked,
+    
+    #[msg("유효하지 않은 키 서명자입니다")]
+    InvalidKeySigner,
+    
+    #[msg("권한이 없는 키 서명자입니다")]
+    UnauthorizedKeySigner,
+    
+    #[msg("철회된 키입니다")]
+    RevokedKey,
+    
+    #[msg("만료된 키입니다")]
+    ExpiredKey,
+    
+    #[msg("수수료가 너무 높습니다")]
+    FeeTooHigh,
+    
+    #[msg("윈도우 내 트랜잭션 한도를 초과했습니다")]
+    WindowTxLimitExceeded,
+    
+    #[msg("트랜잭션 금액 한도를 초과했습니다")]
+    TxAmountLimitExceeded,
+    
+    #[msg("윈도우 내 금액 한도를 초과했습니다")]
+    WindowAmountLimitExceeded,
+    
+    #[msg("허용되지 않은 함수입니다")]
+    FunctionNotAllowed,
+    
+    #[msg("유효하지 않은 세션 권한 범위입니다")]
+    InvalidSessionScope,
+    
+    #[msg("세션 권한 범위에 없는 프로그램입니다")]
+    ProgramNotInScope,
+    
+    #[msg("세션 권한 범위에 없는 함수입니다")]
+    FunctionNotInScope,
+    
+    #[msg("세션 지출 한도를 초과했습니다")]
+    SessionSpendLimitExceeded,
+    
+    #[msg("세션 사용 횟수를 초과했습니다")]
+    SessionUseLimitExceeded,
+    
+    #[msg("사용자별 최대 세션 수에 도달했습니다")]
+    SessionLimitReached,
+    
+    #[msg("세션 레지스트리에서 세션을 찾을 수 없습니다")]
+    SessionNotFound,
+    
+    #[msg("세션 레지스트리의 모든 세션이 전달되지 않았습니다")]
+    IncompleteSessionList,
+    
+    #[msg("유효하지 않은 사용자 계정입니다")]
+    InvalidUserAccount,
+    
+    #[msg("유효하지 않은 만료 시간입니다")]
+    InvalidExpiry,
+    
+    #[msg("아직 활성 상태인 세션입니다")]
+    SessionStillActive,
+    
+    #[msg("원래 지불자 계정이 일치하지 않습니다")]
+    PayerMismatch,
+    
+    #[msg("유효하지 않은 추가 계정 목록입니다")]
+    InvalidRemainingAccounts,
+    
+    #[msg("유효하지 않은 인텐트 서명입니다")]
+    InvalidIntentSignature,
+    
+    #[msg("논스가 일치하지 않습니다")]
+    NonceMismatch,
+    
+    #[msg("요청 유효 기한이 지났습니다")]
+    DeadlineExceeded,
+    
+    #[msg("유효하지 않은 가디언 설정입니다")]
+    InvalidGuardianConfig,
+    
+    #[msg("가디언이 아닙니다")]
+    NotGuardian,
+    
+    #[msg("이미 진행 중인 복구 요청이 있습니다")]
+    RecoveryAlreadyPending,
+    
+    #[msg("진행 중인 복구 요청이 없습니다")]
+    NoPendingRecovery,
+    
+    #[msg("이미 승인한 가디언입니다")]
+    AlreadyApproved,
+    
+    #[msg("복구 승인 임계값에 도달하지 않았습니다")]
+    RecoveryThresholdNotMet,
+    
+    #[msg("복구 대기 시간이 아직 지나지 않았습니다")]
+    RecoveryTimelockActive,
+    
+    #[msg("릴레이가 일시중지되었습니다")]
+    RelayPaused,
+    
+    #[msg("유효하지 않은 보안 정책입니다")]
+    InvalidSecurityPolicy,
+    
+    #[msg("토큰별 한도 목록이 가득 찼습니다")]
+    MintLimitCapacityReached,
+    
+    #[msg("토큰별 한도를 찾을 수 없습니다")]
+    MintLimitNotFound,
+    
+    #[msg("토큰별 트랜잭션 금액 한도를 초과했습니다")]
+    MintTxLimitExceeded,
+    
+    #[msg("토큰별 윈도우 내 금액 한도를 초과했습니다")]
+    MintWindowLimitExceeded,
+    
+    #[msg("허용되지 않은 토큰입니다")]
+    MintNotAllowed,
+    
+    #[msg("토큰 계정의 Mint가 일치하지 않습니다")]
+    MintMismatch,
+    
+    #[msg("유효하지 않은 수신자 정책입니다")]
+    InvalidRecipientPolicy,
+    
+    #[msg("허용 목록에 없는 수신자입니다")]
+    RecipientNotAllowed,
+    
+    #[msg("차단된 수신자입니다")]
+    RecipientDenied,
+    
+    #[msg("트랜잭션 간 최소 간격이 지나지 않았습니다")]
+    TxCooldownActive,
+    
+    #[msg("허용된 시간대가 아닙니다")]
+    OutsideAllowedHours,
+    
+    #[msg("대기 중인 정책 변경이 없습니다")]
+    NoPendingPolicyChange,
+    
+    #[msg("정책 변경 대기 시간이 지나지 않았습니다")]
+    PolicyTimelockActive,
+    
+    #[msg("허용되지 않은 프로그램 및 함수 호출입니다")]
+    CallNotAllowed,
+    
+    #[msg("긴급 동결된 세션입니다")]
+    SessionFrozen,
+    
+    #[msg("동결 상태가 아닙니다")]
+    NotFrozen,
+    
+    #[msg("동결 해제가 요청되지 않았습니다")]
+    UnfreezeNotRequested,
+    
+    #[msg("동결 해제 대기 시간이 지나지 않았습니다")]
+    UnfreezeTimelockActive,
+    
+    #[msg("수수료 수금자 계정이 수수료 정책과 일치하지 않습니다")]
+    FeeCollectorMismatch,
+    
+    #[msg("유효하지 않은 수수료 구간 설정입니다")]
+    InvalidFeeTiers,
+    
+    #[msg("최대 수수료는 최소 수수료 이상이어야 합니다")]
+    InvalidFeeCap,
+    
+    #[msg("유효하지 않은 수수료 분배 대상 설정입니다")]
+    InvalidFeeRecipients,
+    
+    #[msg("수수료 분배 대상 계정이 수수료 정책과 일치하지 않습니다")]
+    FeeRecipientMismatch,
+    
+    #[msg("유효하지 않은 페이마스터 후원 한도입니다")]
+    InvalidPaymasterBudget,
+    
+    #[msg("페이마스터 후원 대상으로 등록되지 않은 사용자입니다")]
+    PaymasterUsageMissing,
+    
+    #[msg("페이마스터 일일 후원 한도를 초과했습니다")]
+    PaymasterDailyBudgetExceeded,
+    
+    #[msg("페이마스터 사용자별 일일 후원 한도를 초과했습니다")]
+    PaymasterUserBudgetExceeded,
     
-    /// 수수료 수금자 계정
-    /// CHECK: 수수료를 받을 계정
-    pub fee_collector: AccountInfo<'info>,
+    #[msg("페이마스터 잔액이 부족합니다")]
+    PaymasterInsufficientFunds,
     
-    /// 수수료 정책 데이터
-    #[account(
-        init_if_needed,
-        payer = authority,
-        seeds = [b"fee_policy"],
-        bump,
-        space = 8 + 32 + 2 + 2 + 8 + 32
-    )]
-    pub fee_policy: Account<'info, FeePolicy>,
+    #[msg("유효하지 않은 함수 ID입니다")]
+    InvalidFunctionId,
     
-    /// 시스템 프로그램
-    pub system_program: Program<'info, System>,
-}
-
-/// 토큰별 수수료 정책 설정 명령어 계정 구조체
-#[derive(Accounts)]
-pub struct SetTokenFeePolicy<'info> {
-    /// 권한 있는 사용자 (관리자)
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    #[msg("대상 프로그램이 함수 ID의 라우팅 프로그램과 일치하지 않습니다")]
+    TargetProgramMismatch,
     
-    /// 토큰 Mint 주소
-    /// CHECK: 토큰 민트는 단순 식별자로만 사용됩니다
-    pub token_mint: AccountInfo<'info>,
+    #[msg("수신자 정책 변경을 적용하려면 수신자 정책 계정이 필요합니다")]
+    RecipientPolicyMissing,
     
-    /// 토큰별 수수료 정책 데이터
-    #[account(
-        init_if_needed,
-        payer = authority,
-        seeds = [b"token_fee_policy", token_mint.key().as_ref()],
-        bump,
-        space = 8 + 32 + 2
-    )]
-    pub token_fee_policy: Account<'info, TokenFeePolicy>,
+    #[msg("마이그레이션 대상인 이전 버전 계정이 아닙니다")]
+    NotLegacyAccount,
     
-    /// 시스템 프로그램
-    pub system_program: Program<'info, System>,
+    #[msg("같은 세션 계정이 중복 전달되었습니다")]
+    DuplicateSessionAccount,
 }
 
-/// 보안 정책 설정 명령어 계정 구조체
-#[derive(Accounts)]
-pub struct SetSecurityPolicy<'info> {
-    /// 권한 있는 사용자 (관리자)
-    #[account(mut)]
-    pub authority: Signer<'info>,
+#[cfg(test)]
+mod tests {
+    use super::*;
     
-    /// 임시 키 매핑 데이터
-    pub key_mapping: Account<'info, KeyMapping>,
+    /// 테스트용 계정 데이터 (AccountInfo가 빌려 쓸 소유 값)
+    struct TestAccount {
+        key: Pubkey,
+        owner: Pubkey,
+        lamports: u64,
+        data: Vec<u8>,
+    }
     
-    /// 보안 정책 데이터
-    #[account(
-        init_if_needed,
-        payer = authority,
-        seeds = [b"security_policy", key_mapping.user_id.as_bytes()],
-        bump,
-        space = 8 + 4 + key_mapping.user_id.len() + 4 + 8 + 8 + 4 + 8 + 8 + 4 + 10 * 1 // 최대 10개의 함수 ID 허용
-    )]
-    pub security_policy: Account<'info, SecurityPolicy>,
+    impl TestAccount {
+        fn new<T: AccountSerialize>(key: Pubkey, account: &T) -> Self {
+            let mut data = Vec::new();
+            account.try_serialize(&mut data).unwrap();
+            Self::raw(key, crate::ID, data)
+        }
+        
+        fn raw(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> Self {
+            Self { key, owner, lamports: 1_000_000_000, data }
+        }
+        
+        fn info(&mut self) -> AccountInfo<'_> {
+            AccountInfo::new(
+                &self.key,
+                false,
+                true,
+                &mut self.lamports,
+                &mut self.data,
+                &self.owner,
+                false,
+                0,
+            )
+        }
+    }
     
-    /// 시스템 프로그램
-    pub system_program: Program<'info, System>,
-}
-
-/// 임시 키 및 백업 키 등록 명령어 계정 구조체
-#[derive(Accounts)]
-#[instruction(user_id: String, expires_at: i64)]
-pub struct RegisterTempKeys<'info> {
-    /// 트랜잭션 지불자 (관리자)
-    #[account(mut)]
-    pub payer: Signer<'info>,
+    fn key_mapping(temp_key: Pubkey, user_id: &str, expires_at: i64) -> KeyMapping {
+        KeyMapping {
+            temp_key,
+            backup_key: Pubkey::new_unique(),
+            user_id: user_id.to_string(),
+            user_account_pda: Pubkey::new_unique(),
+            expires_at,
+            revoked: false,
+            created_at: 0,
+            scope: SessionScope::default(),
+            spent_amount: 0,
+            use_count: 0,
+            payer: Pubkey::new_unique(),
+            nonce: 0,
+            frozen: false,
+            usage: SessionUsage::default(),
+        }
+    }
+    
+    fn key_mapping_account(temp_key: Pubkey, user_id: &str, expires_at: i64) -> TestAccount {
+        let (pda, _) = Pubkey::find_program_address(&[b"key_mapping", temp_key.as_ref()], &crate::ID);
+        TestAccount::new(pda, &key_mapping(temp_key, user_id, expires_at))
+    }
+    
+    fn session_registry(user_id: &str, temp_keys: &[Pubkey], expires_at: i64) -> SessionRegistry {
+        SessionRegistry {
+            user_id: user_id.to_string(),
+            sessions: temp_keys
+                .iter()
+                .map(|temp_key| SessionEntry { temp_key: *temp_key, expires_at, created_at: 0 })
+                .collect(),
+            frozen: false,
+            unfreeze_at: None,
+        }
+    }
+    
+    #[test]
+    fn load_active_key_mappings_accepts_every_active_session() {
+        let temp_keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let registry = session_registry("alice", &temp_keys, 100);
+        let mut first = key_mapping_account(temp_keys[0], "alice", 100);
+        let mut second = key_mapping_account(temp_keys[1], "alice", 100);
+        let infos = [first.info(), second.info()];
+        
+        let key_mappings = load_active_key_mappings(&infos, &registry, 10, &crate::ID).unwrap();
+        assert_eq!(key_mappings.len(), 2);
+    }
+    
+    #[test]
+    fn load_active_key_mappings_rejects_duplicate_accounts() {
+        let temp_keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let registry = session_registry("alice", &temp_keys, 100);
+        let mut first = key_mapping_account(temp_keys[0], "alice", 100);
+        let info = first.info();
+        // 두 번째 세션 대신 첫 번째 세션을 한 번 더 전달하면 개수는 맞지만 두 번째 세션이 누락됨
+        let infos = [info.clone(), info];
+        
+        let err = load_active_key_mappings(&infos, &registry, 10, &crate::ID).map(|_| ()).unwrap_err();
+        assert_eq!(err, ErrorCode::DuplicateSessionAccount.into());
+    }
+    
+    #[test]
+    fn load_active_key_mappings_rejects_missing_sessions() {
+        let temp_keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let registry = session_registry("alice", &temp_keys, 100);
+        let mut first = key_mapping_account(temp_keys[0], "alice", 100);
+        let infos = [first.info()];
+        
+        let err = load_active_key_mappings(&infos, &registry, 10, &crate::ID).map(|_| ()).unwrap_err();
+        assert_eq!(err, ErrorCode::IncompleteSessionList.into());
+    }
+    
+    #[test]
+    fn load_active_key_mappings_rejects_other_users_session() {
+        let temp_keys = [Pubkey::new_unique()];
+        let registry = session_registry("alice", &temp_keys, 100);
+        let mut other = key_mapping_account(temp_keys[0], "mallory", 100);
+        let infos = [other.info()];
+        
+        let err = load_active_key_mappings(&infos, &registry, 10, &crate::ID).map(|_| ()).unwrap_err();
+        assert_eq!(err, ErrorCode::SessionNotFound.into());
+    }
+    
+    #[test]
+    fn revoke_all_requires_the_owner_or_every_sessions_backup_key() {
+        let owner = Pubkey::new_unique();
+        let backup_key = Pubkey::new_unique();
+        let user_authority = user_authority(owner);
+        
+        // 소유자는 활성 세션이 없어도 철회 가능
+        assert!(check_revoke_all_authority(&owner, &user_authority, &[]).is_ok());
+        assert!(check_revoke_all_authority(&owner, &user_authority, &[backup_key]).is_ok());
+        
+        assert!(check_revoke_all_authority(&backup_key, &user_authority, &[backup_key, backup_key]).is_ok());
+        // 활성 세션이 없으면 누구도 백업 키로 인정되지 않음
+        assert!(matches!(
+            check_revoke_all_authority(&backup_key, &user_authority, &[]),
+            Err(ErrorCode::Unauthorized)
+        ));
+        // 다른 세션의 백업 키가 다르면 일괄 철회 불가
+        assert!(matches!(
+            check_revoke_all_authority(&backup_key, &user_authority, &[backup_key, Pubkey::new_unique()]),
+            Err(ErrorCode::Unauthorized)
+        ));
+    }
+    
+    #[test]
+    fn load_registered_key_mappings_includes_expired_sessions() {
+        let temp_keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut registry = session_registry("alice", &temp_keys, 100);
+        registry.sessions[1].expires_at = 5;
+        let mut active = key_mapping_account(temp_keys[0], "alice", 100);
+        let mut expired = key_mapping_account(temp_keys[1], "alice", 5);
+        
+        // 만료되었지만 레지스트리에 남은 세션도 동결 대상에서 빠질 수 없음
+        let infos = [active.info()];
+        let err = load_registered_key_mappings(&infos, &registry, &crate::ID).map(|_| ()).unwrap_err();
+        assert_eq!(err, ErrorCode::IncompleteSessionList.into());
+        
+        let infos = [active.info(), expired.info()];
+        assert_eq!(load_registered_key_mappings(&infos, &registry, &crate::ID).unwrap().len(), 2);
+        // 활성 세션만 요구하는 명령어는 만료된 세션을 받지 않음
+        let err = load_active_key_mappings(&infos, &registry, 10, &crate::ID).map(|_| ()).unwrap_err();
+        assert_eq!(err, ErrorCode::IncompleteSessionList.into());
+    }
+    
+    #[test]
+    fn emergency_freeze_accepts_owner_guardians_and_registered_backup_keys() {
+        let owner = Pubkey::new_unique();
+        let guardian = Pubkey::new_unique();
+        let backup_key = Pubkey::new_unique();
+        let user_authority = user_authority(owner);
+        let guardian_set = GuardianSet {
+            user_id: "alice".to_string(),
+            guardians: vec![guardian],
+            threshold: 1,
+            recovery_delay: 60,
+            pending_recovery: None,
+        };
+        
+        // 활성 세션이 없어도 소유자와 가디언은 동결 가능
+        assert!(is_emergency_freeze_authority(&owner, &user_authority, None, &[]));
+        assert!(is_emergency_freeze_authority(&guardian, &user_authority, Some(&guardian_set), &[]));
+        assert!(is_emergency_freeze_authority(&backup_key, &user_authority, None, &[backup_key]));
+        
+        assert!(!is_emergency_freeze_authority(&guardian, &user_authority, None, &[]));
+        assert!(!is_emergency_freeze_authority(
+            &Pubkey::new_unique(),
+            &user_authority,
+            Some(&guardian_set),
+            &[backup_key]
+        ));
+    }
+    
+    #[test]
+    fn recovery_cancel_requires_an_active_mapping_backup_key() {
+        let mut key_mapping = key_mapping(Pubkey::new_unique(), "alice", 100);
+        let backup_key = key_mapping.backup_key;
+        
+        assert!(check_recovery_cancel_authority(&backup_key, &key_mapping, 10).is_ok());
+        assert!(matches!(
+            check_recovery_cancel_authority(&Pubkey::new_unique(), &key_mapping, 10),
+            Err(ErrorCode::Unauthorized)
+        ));
+        
+        // 만료되었거나 철회된 키 매핑에 남은 이전 백업 키로는 취소 불가
+        assert!(matches!(
+            check_recovery_cancel_authority(&backup_key, &key_mapping, 100),
+            Err(ErrorCode::SessionExpired)
+        ));
+        key_mapping.revoked = true;
+        assert!(matches!(
+            check_recovery_cancel_authority(&backup_key, &key_mapping, 10),
+            Err(ErrorCode::RevokedKey)
+        ));
+    }
+    
+    #[test]
+    fn recovery_loads_only_the_users_inactive_mappings() {
+        let mut expired = key_mapping_account(Pubkey::new_unique(), "alice", 10);
+        let key_mapping = load_inactive_key_mapping(&expired.info(), "alice", 10, &crate::ID).unwrap();
+        assert_eq!(key_mapping.user_id, "alice");
+        
+        let mut active = key_mapping_account(Pubkey::new_unique(), "alice", 100);
+        let err = load_inactive_key_mapping(&active.info(), "alice", 10, &crate::ID)
+            .map(|_| ())
+            .unwrap_err();
+        assert_eq!(err, ErrorCode::SessionStillActive.into());
+        
+        let mut other = key_mapping_account(Pubkey::new_unique(), "mallory", 10);
+        let err = load_inactive_key_mapping(&other.info(), "alice", 10, &crate::ID)
+            .map(|_| ())
+            .unwrap_err();
+        assert_eq!(err, ErrorCode::SessionNotFound.into());
+    }
+    
+    #[test]
+    fn session_scope_allows_unrestricted_session() {
+        let key_mapping = key_mapping(Pubkey::new_unique(), "alice", 100);
+        assert!(check_session_scope(&key_mapping, &Pubkey::new_unique(), 7, u64::MAX).is_ok());
+    }
+    
+    #[test]
+    fn session_scope_enforces_programs_and_functions() {
+        let user_account_program = user_account::UserAccount::id();
+        let mut key_mapping = key_mapping(Pubkey::new_unique(), "alice", 100);
+        key_mapping.scope.allowed_programs = vec![user_account_program];
+        key_mapping.scope.allowed_functions = vec![FUNCTION_TRANSFER];
+        
+        assert!(check_session_scope(&key_mapping, &user_account_program, FUNCTION_TRANSFER, 0).is_ok());
+        assert!(matches!(
+            check_session_scope(&key_mapping, &system_program::ID, FUNCTION_TRANSFER, 0),
+            Err(ErrorCode::ProgramNotInScope)
+        ));
+        assert!(matches!(
+            check_session_scope(&key_mapping, &user_account_program, FUNCTION_CREATE_SWAP, 0),
+            Err(ErrorCode::FunctionNotInScope)
+        ));
+    }
+    
+    #[test]
+    fn session_scope_enforces_spend_limit_and_max_uses() {
+        let program = user_account::UserAccount::id();
+        let mut key_mapping = key_mapping(Pubkey::new_unique(), "alice", 100);
+        key_mapping.scope.spend_limit = 1_000;
+        key_mapping.scope.max_uses = 2;
+        key_mapping.spent_amount = 600;
+        
+        assert!(check_session_scope(&key_mapping, &program, FUNCTION_TRANSFER, 400).is_ok());
+        assert!(matches!(
+            check_session_scope(&key_mapping, &program, FUNCTION_TRANSFER, 401),
+            Err(ErrorCode::SessionSpendLimitExceeded)
+        ));
+        
+        key_mapping.use_count = 2;
+        assert!(matches!(
+            check_session_scope(&key_mapping, &program, FUNCTION_TRANSFER, 0),
+            Err(ErrorCode::SessionUseLimitExceeded)
+        ));
+    }
+    
+    #[test]
+    fn routed_program_rejects_unknown_function_ids() {
+        for function_id in [FUNCTION_TRANSFER, FUNCTION_REGISTER_TOKEN, FUNCTION_CREATE_SWAP] {
+            assert_eq!(routed_program(function_id).unwrap(), user_account::UserAccount::id());
+        }
+        // 전송 함수 ID는 릴레이 호출 대상이 아니므로 라우팅되지 않음
+        for function_id in [FUNCTION_TRANSFER_SPL_TOKEN, FUNCTION_TRANSFER_SOL, u8::MAX] {
+            assert!(matches!(routed_program(function_id), Err(ErrorCode::InvalidFunctionId)));
+        }
+    }
+    
+    #[test]
+    fn revoke_reason_follows_signer_role() {
+        let key_mapping = key_mapping(Pubkey::new_unique(), "alice", 100);
+        let user_authority = UserAuthority {
+            user_id: "alice".to_string(),
+            owner: Pubkey::new_unique(),
+            created_at: 0,
+        };
+        let relay_config = RelayConfig { admin: Pubkey::new_unique(), pending_admin: None, paused: false };
+        let reason = |authority: &Pubkey, config: Option<&RelayConfig>| {
+            revoke_reason(authority, &key_mapping, &user_authority, config)
+        };
+        
+        assert!(reason(&key_mapping.temp_key, None) == Some(RevokeReason::SelfRevoke));
+        assert!(reason(&key_mapping.backup_key, None) == Some(RevokeReason::BackupKey));
+        assert!(reason(&user_authority.owner, None) == Some(RevokeReason::Owner));
+        assert!(reason(&relay_config.admin, Some(&relay_config)) == Some(RevokeReason::AdminEmergency));
+        // 릴레이 설정 없이는 관리자도 철회할 수 없음
+        assert!(reason(&relay_config.admin, None).is_none());
+        assert!(reason(&Pubkey::new_unique(), Some(&relay_config)).is_none());
+    }
+    
+    #[test]
+    fn removed_sessions_leave_the_registry() {
+        let temp_keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut registry = session_registry("alice", &temp_keys, 100);
+        
+        remove_session(&mut registry, &temp_keys[0]);
+        assert_eq!(registry.sessions.len(), 1);
+        assert_eq!(registry.sessions[0].temp_key, temp_keys[1]);
+        
+        // 철회된 세션은 일괄 철회 시 전달 대상에서 제외됨
+        let mut second = key_mapping_account(temp_keys[1], "alice", 100);
+        let infos = [second.info()];
+        assert_eq!(load_active_key_mappings(&infos, &registry, 10, &crate::ID).unwrap().len(), 1);
+    }
+    
+    fn policy_limits() -> PolicyLimits {
+        PolicyLimits {
+            window_seconds: WINDOW_HOURLY,
+            max_tx_per_window: 100,
+            max_amount_per_tx: u64::MAX,
+            max_amount_per_window: u64::MAX,
+            min_tx_interval: 0,
+            allowed_hour_start: 0,
+            allowed_hour_end: 0,
+            utc_offset_minutes: 0,
+            default_mint_allowed: true,
+            loosen_delay: 0,
+            allowed_functions: Vec::new(),
+            allowed_calls: Vec::new(),
+        }
+    }
+    
+    fn security_policy(limits: PolicyLimits) -> SecurityPolicy {
+        SecurityPolicy {
+            user_id: "alice".to_string(),
+            limits,
+            tx_usage: 0,
+            amount_usage: 0,
+            last_update: 0,
+            last_tx_at: 0,
+            mint_limits: Vec::new(),
+            pending_change: None,
+        }
+    }
+    
+    fn apply(
+        security_policy: &mut SecurityPolicy,
+        mint: Option<Pubkey>,
+        amount: u64,
+        now: i64,
+    ) -> std::result::Result<(), ErrorCode> {
+        let program = user_account::UserAccount::id();
+        apply_security_policy(security_policy, &program, FUNCTION_TRANSFER, mint, amount, now)
+    }
+    
+    #[test]
+    fn rolling_window_limits_transaction_count() {
+        let mut limits = policy_limits();
+        limits.max_tx_per_window = 2;
+        let mut policy = security_policy(limits);
+        
+        assert!(apply(&mut policy, None, 0, 0).is_ok());
+        assert!(apply(&mut policy, None, 0, 0).is_ok());
+        assert!(matches!(apply(&mut policy, None, 0, 0), Err(ErrorCode::WindowTxLimitExceeded)));
+        
+        // 윈도우 절반이 지나면 한도의 절반(1건)이 회복됨
+        assert!(apply(&mut policy, None, 0, WINDOW_HOURLY / 2).is_ok());
+        assert!(matches!(
+            apply(&mut policy, None, 0, WINDOW_HOURLY / 2),
+            Err(ErrorCode::WindowTxLimitExceeded)
+        ));
+    }
+    
+    #[test]
+    fn rolling_window_limits_amount_and_recovers_gradually() {
+        let mut limits = policy_limits();
+        limits.max_amount_per_tx = 800;
+        limits.max_amount_per_window = 1_000;
+        let mut policy = security_policy(limits);
+        
+        assert!(matches!(apply(&mut policy, None, 801, 0), Err(ErrorCode::TxAmountLimitExceeded)));
+        assert!(apply(&mut policy, None, 600, 0).is_ok());
+        assert!(matches!(apply(&mut policy, None, 401, 0), Err(ErrorCode::WindowAmountLimitExceeded)));
+        assert!(apply(&mut policy, None, 400, 0).is_ok());
+        
+        // 윈도우의 1/4이 지나면 250만큼만 다시 사용 가능
+        assert!(matches!(
+            apply(&mut policy, None, 251, WINDOW_HOURLY / 4),
+            Err(ErrorCode::WindowAmountLimitExceeded)
+        ));
+        assert!(apply(&mut policy, None, 250, WINDOW_HOURLY / 4).is_ok());
+    }
+    
+    #[test]
+    fn rejected_transactions_do_not_consume_usage() {
+        let mut limits = policy_limits();
+        limits.max_amount_per_window = 1_000;
+        let mut policy = security_policy(limits);
+        
+        assert!(apply(&mut policy, None, 1_001, 0).is_err());
+        assert_eq!(policy.tx_usage, 0);
+        assert_eq!(policy.amount_usage, 0);
+        assert!(apply(&mut policy, None, 1_000, 0).is_ok());
+    }
+    
+    #[test]
+    fn allowed_hours_support_same_day_and_overnight_ranges() {
+        let mut limits = policy_limits();
+        
+        // 시작과 종료가 같으면 제한 없음
+        assert!((0..24).all(|hour| is_hour_allowed(&limits, hour)));
+        
+        limits.allowed_hour_start = 9;
+        limits.allowed_hour_end = 18;
+        assert!(!is_hour_allowed(&limits, 8));
+        assert!(is_hour_allowed(&limits, 9));
+        assert!(is_hour_allowed(&limits, 17));
+        assert!(!is_hour_allowed(&limits, 18));
+        
+        // 자정을 넘는 구간 (22시 ~ 다음 날 6시)
+        limits.allowed_hour_start = 22;
+        limits.allowed_hour_end = 6;
+        assert!(!is_hour_allowed(&limits, 21));
+        assert!(is_hour_allowed(&limits, 22));
+        assert!(is_hour_allowed(&limits, 0));
+        assert!(is_hour_allowed(&limits, 5));
+        assert!(!is_hour_allowed(&limits, 6));
+    }
+    
+    #[test]
+    fn allowed_hours_apply_negative_utc_offsets() {
+        let mut limits = policy_limits();
+        limits.allowed_hour_start = 9;
+        limits.allowed_hour_end = 18;
+        limits.utc_offset_minutes = -300;
+        
+        // UTC 14:00 = 현지(UTC-5) 09:00
+        assert!(is_within_allowed_hours(&limits, 14 * 3_600));
+        assert!(!is_within_allowed_hours(&limits, 14 * 3_600 - 1));
+        
+        // UTC 02:00 = 현지 전날 21:00 (날짜 경계를 넘는 음수 오프셋)
+        limits.allowed_hour_start = 20;
+        limits.allowed_hour_end = 6;
+        assert!(is_within_allowed_hours(&limits, 2 * 3_600));
+        assert!(is_within_allowed_hours(&limits, 3_600));
+        assert!(!is_within_allowed_hours(&limits, 3_600 - 1));
+        
+        let mut policy = security_policy(limits);
+        assert!(matches!(
+            apply(&mut policy, None, 0, 12 * 3_600),
+            Err(ErrorCode::OutsideAllowedHours)
+        ));
+        assert!(apply(&mut policy, None, 0, 2 * 3_600).is_ok());
+    }
+    
+    #[test]
+    fn min_tx_interval_enforces_a_cooldown() {
+        let mut limits = policy_limits();
+        limits.min_tx_interval = 60;
+        let mut policy = security_policy(limits);
+        
+        assert!(apply(&mut policy, None, 0, 1_000).is_ok());
+        assert!(matches!(apply(&mut policy, None, 0, 1_059), Err(ErrorCode::TxCooldownActive)));
+        // 거부된 트랜잭션은 마지막 트랜잭션 시각을 갱신하지 않음
+        assert_eq!(policy.last_tx_at, 1_000);
+        assert!(apply(&mut policy, None, 0, 1_060).is_ok());
+        assert_eq!(policy.last_tx_at, 1_060);
+    }
+    
+    #[test]
+    fn policy_check_reports_reason_and_remaining_quotas() {
+        let usdc = Pubkey::new_unique();
+        let mut limits = policy_limits();
+        limits.max_tx_per_window = 5;
+        limits.max_amount_per_tx = 300;
+        limits.max_amount_per_window = 1_000;
+        let mut policy = security_policy(limits);
+        assert!(apply(&mut policy, None, 200, 0).is_ok());
+        
+        let mut simulated = policy.clone();
+        let decision = apply(&mut simulated, None, 100, 0);
+        let result = policy_check_result(&policy, decision, None, 0);
+        assert!(result.allowed && result.policy_exists);
+        assert_eq!(result.reason_code, 0);
+        // 남은 한도는 이번 요청을 반영하기 전 기준
+        assert_eq!(result.remaining_tx, 4);
+        assert_eq!(result.remaining_amount, 800);
+        assert_eq!(result.max_amount_per_tx, 300);
+        assert_eq!(result.remaining_mint_amount, None);
+        
+        // 거부 사유 코드는 실제 호출 시 반환될 Anchor 오류 코드
+        policy.mint_limits = vec![mint_limit(usdc, 500, 1_000)];
+        let decision = apply(&mut policy.clone(), Some(usdc), 501, 0);
+        let result = policy_check_result(&policy, decision, Some(usdc), 0);
+        assert!(!result.allowed);
+        assert_eq!(
+            result.reason_code,
+            ErrorCode::MintTxLimitExceeded as u32 + anchor_lang::error::ERROR_CODE_OFFSET
+        );
+        assert_eq!(result.remaining_mint_amount, Some(1_000));
+    }
+    
+    #[test]
+    fn policy_check_return_data_layout() {
+        let result = PolicyCheckResult {
+            allowed: false,
+            reason_code: 6_001,
+            remaining_tx: 2,
+            remaining_amount: 3,
+            max_amount_per_tx: 4,
+            remaining_mint_amount: Some(5),
+            policy_exists: true,
+        };
+        
+        // 클라이언트가 반환 데이터를 직접 해석하므로 Borsh 배치가 바뀌지 않아야 함
+        let mut expected = vec![0u8];
+        expected.extend_from_slice(&6_001u32.to_le_bytes());
+        expected.extend_from_slice(&2u32.to_le_bytes());
+        expected.extend_from_slice(&3u64.to_le_bytes());
+        expected.extend_from_slice(&4u64.to_le_bytes());
+        expected.push(1);
+        expected.extend_from_slice(&5u64.to_le_bytes());
+        expected.push(1);
+        assert_eq!(result.try_to_vec().unwrap(), expected);
+    }
+    
+    #[test]
+    fn policy_check_without_a_policy_is_unrestricted() {
+        let mut missing = TestAccount::raw(Pubkey::new_unique(), system_program::ID, Vec::new());
+        assert!(load_security_policy(&missing.info(), &crate::ID).unwrap().is_none());
+        
+        let result = unrestricted_policy_check_result();
+        assert!(result.allowed && !result.policy_exists);
+        assert_eq!(result.reason_code, 0);
+        assert_eq!(result.remaining_tx, u32::MAX);
+        assert_eq!(result.remaining_amount, u64::MAX);
+        assert_eq!(result.max_amount_per_tx, u64::MAX);
+        assert_eq!(result.remaining_mint_amount, None);
+    }
+    
+    fn mint_limit(mint: Pubkey, max_amount_per_tx: u64, max_amount_per_window: u64) -> MintLimit {
+        MintLimit { mint, max_amount_per_tx, max_amount_per_window, usage: 0, last_update: 0 }
+    }
+    
+    #[test]
+    fn per_mint_limits_are_tracked_separately() {
+        let usdc = Pubkey::new_unique();
+        let bonk = Pubkey::new_unique();
+        let mut policy = security_policy(policy_limits());
+        policy.mint_limits = vec![mint_limit(usdc, 500, 1_000), mint_limit(bonk, 10_000, 10_000)];
+        
+        assert!(matches!(apply(&mut policy, Some(usdc), 501, 0), Err(ErrorCode::MintTxLimitExceeded)));
+        assert!(apply(&mut policy, Some(usdc), 500, 0).is_ok());
+        assert!(apply(&mut policy, Some(usdc), 500, 0).is_ok());
+        assert!(matches!(apply(&mut policy, Some(usdc), 1, 0), Err(ErrorCode::MintWindowLimitExceeded)));
+        // 다른 토큰의 한도는 영향을 받지 않음
+        assert!(apply(&mut policy, Some(bonk), 10_000, 0).is_ok());
+        // 윈도우 절반이 지나면 해당 토큰 한도의 절반이 회복됨
+        assert!(apply(&mut policy, Some(usdc), 500, WINDOW_HOURLY / 2).is_ok());
+    }
+    
+    #[test]
+    fn unlisted_mints_follow_default_mint_allowed() {
+        let mut limits = policy_limits();
+        limits.default_mint_allowed = false;
+        let mut policy = security_policy(limits);
+        policy.mint_limits = vec![mint_limit(Pubkey::new_unique(), 500, 1_000)];
+        
+        assert!(matches!(
+            apply(&mut policy, Some(Pubkey::new_unique()), 1, 0),
+            Err(ErrorCode::MintNotAllowed)
+        ));
+        // 토큰 전송이 아닌 호출은 토큰별 한도와 무관함
+        assert!(apply(&mut policy, None, 0, 0).is_ok());
+    }
+    
+    #[test]
+    fn global_amount_limits_skip_only_mints_with_their_own_limit() {
+        let usdc = Pubkey::new_unique();
+        let native_mint = token::spl_token::native_mint::ID;
+        let mut limits = policy_limits();
+        limits.max_amount_per_tx = 100;
+        limits.max_amount_per_window = 100;
+        let mut policy = security_policy(limits);
+        
+        // 토큰별 한도가 없으면 모든 토큰에 전역 한도 적용
+        assert!(applies_global_amount_limits(&policy, Some(usdc)));
+        assert!(matches!(apply(&mut policy, Some(usdc), 101, 0), Err(ErrorCode::TxAmountLimitExceeded)));
+        
+        policy.mint_limits = vec![mint_limit(usdc, 1_000, 1_000)];
+        assert!(!applies_global_amount_limits(&policy, Some(usdc)));
+        assert!(applies_global_amount_limits(&policy, Some(Pubkey::new_unique())));
+        assert!(applies_global_amount_limits(&policy, Some(native_mint)));
+        assert!(applies_global_amount_limits(&policy, None));
+        
+        // 한도가 설정된 토큰은 토큰별 한도만, SOL 전송은 전역 한도를 사용
+        assert!(apply(&mut policy, Some(usdc), 1_000, 0).is_ok());
+        assert_eq!(policy.amount_usage, 0);
+        assert!(matches!(
+            apply(&mut policy, Some(native_mint), 101, 0),
+            Err(ErrorCode::TxAmountLimitExceeded)
+        ));
+        assert!(apply(&mut policy, Some(native_mint), 100, 0).is_ok());
+    }
+    
+    fn user_authority(owner: Pubkey) -> UserAuthority {
+        UserAuthority { user_id: "alice".to_string(), owner, created_at: 0 }
+    }
+    
+    fn recipient_policy(mode: RecipientMode, recipients: Vec<Pubkey>) -> RecipientPolicy {
+        RecipientPolicy { user_id: "alice".to_string(), mode, recipients }
+    }
+    
+    #[test]
+    fn loosening_limits_are_detected_per_field() {
+        let old = policy_limits();
+        assert!(!is_loosening_limits(&old, &old));
+        
+        let mut tighter = policy_limits();
+        tighter.max_tx_per_window = 10;
+        tighter.min_tx_interval = 60;
+        assert!(!is_loosening_limits(&old, &tighter));
+        assert!(is_loosening_limits(&tighter, &old));
+        
+        // 같은 한도를 더 짧은 윈도우로 바꾸면 회복 속도가 빨라지므로 완화
+        let mut faster = tighter.clone();
+        faster.window_seconds = 60;
+        assert!(is_loosening_limits(&tighter, &faster));
+        
+        let mut shorter_delay = policy_limits();
+        shorter_delay.loosen_delay = -1;
+        assert!(is_loosening_limits(&old, &shorter_delay));
+    }
+    
+    #[test]
+    fn loosening_changes_wait_for_the_timelock() {
+        let mut limits = policy_limits();
+        limits.max_tx_per_window = 10;
+        limits.loosen_delay = DEFAULT_LOOSEN_DELAY;
+        let mut policy = security_policy(limits.clone());
+        
+        let mut looser = limits.clone();
+        looser.max_tx_per_window = 20;
+        let loosening = is_loosening_limits(&policy.limits, &looser);
+        assert!(stage_policy_change(&mut policy, PolicyChange::Limits(looser), loosening, 100).is_ok());
+        assert_eq!(policy.limits.max_tx_per_window, 10);
+        assert_eq!(policy.pending_change.as_ref().unwrap().executable_at, 100 + DEFAULT_LOOSEN_DELAY);
+        
+        // 강화 변경은 즉시 적용되고 대기 중인 완화 변경을 폐기함
+        let mut tighter = limits;
+        tighter.max_tx_per_window = 5;
+        let loosening = is_loosening_limits(&policy.limits, &tighter);
+        assert!(stage_policy_change(&mut policy, PolicyChange::Limits(tighter), loosening, 200).is_ok());
+        assert_eq!(policy.limits.max_tx_per_window, 5);
+        assert!(policy.pending_change.is_none());
+    }
+    
+    #[test]
+    fn recipient_policy_modes_allow_and_deny_listed_recipients() {
+        let listed = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        
+        let open = recipient_policy(RecipientMode::Open, vec![listed]);
+        assert!(check_recipient(&open, &[listed]).is_ok());
+        assert!(check_recipient(&open, &[other]).is_ok());
+        
+        let allowlist = recipient_policy(RecipientMode::Allowlist, vec![listed]);
+        assert!(check_recipient(&allowlist, &[listed]).is_ok());
+        assert!(matches!(check_recipient(&allowlist, &[other]), Err(ErrorCode::RecipientNotAllowed)));
+        // 빈 허용 목록은 모든 수신자를 거부
+        let empty_allowlist = recipient_policy(RecipientMode::Allowlist, Vec::new());
+        assert!(matches!(
+            check_recipient(&empty_allowlist, &[listed]),
+            Err(ErrorCode::RecipientNotAllowed)
+        ));
+        
+        let denylist = recipient_policy(RecipientMode::Denylist, vec![listed]);
+        assert!(matches!(check_recipient(&denylist, &[listed]), Err(ErrorCode::RecipientDenied)));
+        assert!(check_recipient(&denylist, &[other]).is_ok());
+    }
+    
+    #[test]
+    fn recipient_policy_matches_token_accounts_by_account_or_owner_wallet() {
+        let wallet = Pubkey::new_unique();
+        let token_account = Pubkey::new_unique();
+        // SPL 전송은 수신 토큰 계정과 그 소유자 지갑을 모두 후보로 전달
+        let candidates = [token_account, wallet];
+        
+        let allow_wallet = recipient_policy(RecipientMode::Allowlist, vec![wallet]);
+        assert!(check_recipient(&allow_wallet, &candidates).is_ok());
+        let allow_token_account = recipient_policy(RecipientMode::Allowlist, vec![token_account]);
+        assert!(check_recipient(&allow_token_account, &candidates).is_ok());
+        
+        // 지갑을 차단하면 해당 지갑 소유의 어떤 토큰 계정으로도 전송 불가
+        let deny_wallet = recipient_policy(RecipientMode::Denylist, vec![wallet]);
+        assert!(matches!(check_recipient(&deny_wallet, &candidates), Err(ErrorCode::RecipientDenied)));
+        
+        // 다른 지갑 소유의 토큰 계정은 허용 목록의 지갑과 일치하지 않음
+        let stranger_candidates = [Pubkey::new_unique(), Pubkey::new_unique()];
+        assert!(matches!(
+            check_recipient(&allow_wallet, &stranger_candidates),
+            Err(ErrorCode::RecipientNotAllowed)
+        ));
+    }
+    
+    #[test]
+    fn recipient_policy_loosening_covers_every_mode_pair() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let open = recipient_policy(RecipientMode::Open, vec![]);
+        let allow_a = recipient_policy(RecipientMode::Allowlist, vec![a]);
+        let deny_a = recipient_policy(RecipientMode::Denylist, vec![a]);
+        
+        assert!(!is_recipient_policy_loosened(&open, RecipientMode::Allowlist, &[a]));
+        assert!(is_recipient_policy_loosened(&allow_a, RecipientMode::Open, &[]));
+        assert!(is_recipient_policy_loosened(&deny_a, RecipientMode::Open, &[]));
+        
+        assert!(is_recipient_policy_loosened(&allow_a, RecipientMode::Allowlist, &[a, b]));
+        assert!(!is_recipient_policy_loosened(&allow_a, RecipientMode::Allowlist, &[]));
+        assert!(is_recipient_policy_loosened(&allow_a, RecipientMode::Denylist, &[a]));
+        
+        assert!(is_recipient_policy_loosened(&deny_a, RecipientMode::Denylist, &[b]));
+        assert!(!is_recipient_policy_loosened(&deny_a, RecipientMode::Denylist, &[a, b]));
+        assert!(is_recipient_policy_loosened(&deny_a, RecipientMode::Allowlist, &[a]));
+        assert!(!is_recipient_policy_loosened(&deny_a, RecipientMode::Allowlist, &[b]));
+    }
+    
+    #[test]
+    fn queued_recipient_policy_change_needs_the_recipient_policy_account() {
+        let a = Pubkey::new_unique();
+        let mut limits = policy_limits();
+        limits.loosen_delay = DEFAULT_LOOSEN_DELAY;
+        let mut policy = security_policy(limits);
+        let change = PolicyChange::SetRecipientPolicy { mode: RecipientMode::Open, recipients: vec![] };
+        assert!(queue_policy_change(&mut policy, change, true, 0).is_none());
+        
+        let pending = policy.pending_change.take().unwrap();
+        assert!(matches!(
+            execute_policy_change(&mut policy, pending.change.clone(), None, DEFAULT_LOOSEN_DELAY),
+            Err(ErrorCode::RecipientPolicyMissing)
+        ));
+        
+        let mut recipients = recipient_policy(RecipientMode::Allowlist, vec![a]);
+        assert!(execute_policy_change(
+            &mut policy,
+            pending.change,
+            Some(&mut recipients),
+            DEFAULT_LOOSEN_DELAY,
+        )
+        .is_ok());
+        assert!(recipients.mode == RecipientMode::Open);
+        assert!(recipients.recipients.is_empty());
+    }
+    
+    #[test]
+    fn policy_change_cancel_requires_an_active_backup_key_or_owner() {
+        let owner = Pubkey::new_unique();
+        let user_authority = user_authority(owner);
+        let mut key_mapping = key_mapping(Pubkey::new_unique(), "alice", 100);
+        let backup_key = key_mapping.backup_key;
+        
+        assert!(check_policy_cancel_authority(&backup_key, &key_mapping, &user_authority).is_ok());
+        assert!(check_policy_cancel_authority(&owner, &key_mapping, &user_authority).is_ok());
+        assert!(matches!(
+            check_policy_cancel_authority(&key_mapping.temp_key, &key_mapping, &user_authority),
+            Err(ErrorCode::Unauthorized)
+        ));
+        
+        // 철회된 키 매핑의 백업 키로는 취소할 수 없지만 소유자는 가능
+        key_mapping.revoked = true;
+        assert!(matches!(
+            check_policy_cancel_authority(&backup_key, &key_mapping, &user_authority),
+            Err(ErrorCode::RevokedKey)
+        ));
+        assert!(check_policy_cancel_authority(&owner, &key_mapping, &user_authority).is_ok());
+    }
+    
+    /// ed25519 프로그램 명령어 데이터 (서명 검증은 ed25519 프로그램이 수행하므로 서명 값은 비워 둠)
+    fn ed25519_instruction_data(
+        signer: &Pubkey,
+        message: &[u8],
+        data_instruction_index: u16,
+    ) -> Vec<u8> {
+        let public_key_offset: u16 = 16;
+        let signature_offset: u16 = public_key_offset + 32;
+        let message_offset: u16 = signature_offset + 64;
+        
+        let mut data = vec![1u8, 0];
+        for value in [
+            signature_offset,
+            data_instruction_index,
+            public_key_offset,
+            data_instruction_index,
+            message_offset,
+            message.len() as u16,
+            data_instruction_index,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[0u8; 64]);
+        data.extend_from_slice(message);
+        data
+    }
+    
+    /// 직전 명령어로 ed25519 명령어를 둔 명령어 sysvar 계정
+    fn instructions_sysvar_account(ed25519_data: &[u8], current_index: u16) -> TestAccount {
+        use anchor_lang::solana_program::sysvar::instructions::{
+            construct_instructions_data, store_current_index, BorrowedInstruction,
+        };
+        
+        let relay_data = [0u8; 8];
+        let mut data = construct_instructions_data(&[
+            BorrowedInstruction { program_id: &ed25519_program::ID, accounts: vec![], data: ed25519_data },
+            BorrowedInstruction { program_id: &crate::ID, accounts: vec![], data: &relay_data },
+        ]);
+        store_current_index(&mut data, current_index);
+        TestAccount::raw(instructions_sysvar::ID, anchor_lang::solana_program::sysvar::ID, data)
+    }
+    
+    fn transfer_intent(nonce: u64, deadline: i64) -> Vec<u8> {
+        intent_message(
+            &crate::ID,
+            &user_account::UserAccount::id(),
+            FUNCTION_TRANSFER,
+            &[1, 2, 3],
+            nonce,
+            deadline,
+        )
+    }
+    
+    #[test]
+    fn intent_message_binds_programs_and_parameters() {
+        let message = transfer_intent(0, 100);
+        assert!(message.starts_with(INTENT_DOMAIN));
+        assert_eq!(message.len(), INTENT_DOMAIN.len() + 32 + 32 + 1 + 4 + 3 + 8 + 8);
+        
+        let target_program = user_account::UserAccount::id();
+        let other_target =
+            intent_message(&crate::ID, &system_program::ID, FUNCTION_TRANSFER, &[1, 2, 3], 0, 100);
+        let other_relay =
+            intent_message(&Pubkey::new_unique(), &target_program, FUNCTION_TRANSFER, &[1, 2, 3], 0, 100);
+        assert_ne!(message, other_target);
+        assert_ne!(message, other_relay);
+        assert_ne!(message, transfer_intent(1, 100));
+        assert_ne!(message, transfer_intent(0, 101));
+    }
+    
+    #[test]
+    fn ed25519_instruction_must_cover_signer_and_message() {
+        let signer = Pubkey::new_unique();
+        let message = transfer_intent(0, 100);
+        let ed25519_data = ed25519_instruction_data(&signer, &message, u16::MAX);
+        let mut sysvar = instructions_sysvar_account(&ed25519_data, 1);
+        let info = sysvar.info();
+        
+        assert!(verify_ed25519_instruction(&info, &signer, &message).is_ok());
+        assert!(matches!(
+            verify_ed25519_instruction(&info, &Pubkey::new_unique(), &message),
+            Err(ErrorCode::InvalidIntentSignature)
+        ));
+        assert!(matches!(
+            verify_ed25519_instruction(&info, &signer, &transfer_intent(1, 100)),
+            Err(ErrorCode::InvalidIntentSignature)
+        ));
+    }
+    
+    #[test]
+    fn ed25519_instruction_must_precede_and_hold_its_own_data() {
+        let signer = Pubkey::new_unique();
+        let message = transfer_intent(0, 100);
+        
+        // 서명 검증 명령어가 직전에 없음
+        let ed25519_data = ed25519_instruction_data(&signer, &message, u16::MAX);
+        let mut first = instructions_sysvar_account(&ed25519_data, 0);
+        assert!(matches!(
+            verify_ed25519_instruction(&first.info(), &signer, &message),
+            Err(ErrorCode::InvalidIntentSignature)
+        ));
+        
+        // 공개키와 메시지를 다른 명령어에서 읽도록 지정한 경우
+        let ed25519_data = ed25519_instruction_data(&signer, &message, 1);
+        let mut elsewhere = instructions_sysvar_account(&ed25519_data, 1);
+        assert!(matches!(
+            verify_ed25519_instruction(&elsewhere.info(), &signer, &message),
+            Err(ErrorCode::InvalidIntentSignature)
+        ));
+    }
+    
+    #[test]
+    fn nonce_is_consumed_exactly_once() {
+        let mut key_mapping = key_mapping(Pubkey::new_unique(), "alice", 100);
+        
+        assert!(consume_nonce(&mut key_mapping, 0, 50, 10).is_ok());
+        assert_eq!(key_mapping.nonce, 1);
+        // 같은 인텐트 재전송
+        assert!(matches!(consume_nonce(&mut key_mapping, 0, 50, 10), Err(ErrorCode::NonceMismatch)));
+        // 아직 사용되지 않은 미래 논스도 거부
+        assert!(matches!(consume_nonce(&mut key_mapping, 2, 50, 10), Err(ErrorCode::NonceMismatch)));
+        assert!(consume_nonce(&mut key_mapping, 1, 50, 10).is_ok());
+        assert_eq!(key_mapping.nonce, 2);
+    }
+    
+    #[test]
+    fn expired_intents_do_not_consume_the_nonce() {
+        let mut key_mapping = key_mapping(Pubkey::new_unique(), "alice", 100);
+        
+        // 기한 시각까지는 유효
+        assert!(consume_nonce(&mut key_mapping, 0, 50, 50).is_ok());
+        assert!(matches!(consume_nonce(&mut key_mapping, 1, 50, 51), Err(ErrorCode::DeadlineExceeded)));
+        assert_eq!(key_mapping.nonce, 1);
+    }
+    
+    #[test]
+    fn freeze_instruction_targets_user_account_set_relay_freeze() {
+        let (relay_authority, _) = Pubkey::find_program_address(&[b"relay_authority"], &crate::ID);
+        let user_account = Pubkey::new_unique();
+        
+        for frozen in [true, false] {
+            let ix = set_relay_freeze_instruction(&relay_authority, &user_account, frozen);
+            assert_eq!(ix.program_id, user_account::UserAccount::id());
+            assert_eq!(ix.data[..8], hash(b"global:set_relay_freeze").to_bytes()[..8]);
+            assert_eq!(ix.data[8..], [frozen as u8]);
+            
+            // 계정 순서는 사용자 계정 프로그램의 RelayOperation과 동일해야 함
+            assert_eq!(ix.accounts.len(), 2);
+            assert!(ix.accounts[0].pubkey == relay_authority && ix.accounts[0].is_signer);
+            assert!(ix.accounts[1].pubkey == user_account && ix.accounts[1].is_writable);
+        }
+    }
     
-    /// 임시 키 계정
-    /// CHECK: 임시 키는 서명 검증이 필요 없음, 단순 식별자로 사용
-    pub temp_key: AccountInfo<'info>,
+    fn fee_recipients(weights: &[u16]) -> Vec<FeeRecipient> {
+        weights
+            .iter()
+            .map(|weight| FeeRecipient { recipient: Pubkey::new_unique(), weight: *weight })
+            .collect()
+    }
     
-    /// 백업 키 계정
-    /// CHECK: 백업 키는 서명 검증이 필요 없음, 단순 식별자로 사용
-    pub backup_key: AccountInfo<'info>,
+    #[test]
+    fn split_fee_gives_the_remainder_to_the_designated_recipient() {
+        let recipients = fee_recipients(&[1, 1, 1]);
+        
+        assert_eq!(split_fee(100, &recipients, 0), vec![34, 33, 33]);
+        assert_eq!(split_fee(100, &recipients, 2), vec![33, 33, 34]);
+        assert_eq!(split_fee(2, &recipients, 1), vec![0, 2, 0]);
+    }
     
-    /// 임시 키 매핑 데이터
-    #[account(
-        init,
-        payer = payer,
-        seeds = [
-            b"key_mapping",
-            temp_key.key().as_ref(),
-        ],
-        bump,
-        // 정확한 공간 계산
-        space = 8 + // 디스크리미네이터
-               32 + // temp_key: Pubkey
-               32 + // backup_key: Pubkey
-               4 + user_id.len() + // user_id: String (길이 헤더 + 데이터)
-               32 + // user_account_pda: Pubkey
-               8 +  // expires_at: i64
-               1 +  // revoked: bool
-               8    // created_at: i64
-    )]
-    pub key_mapping: Account<'info, KeyMapping>,
+    #[test]
+    fn split_fee_follows_weights_and_never_loses_lamports() {
+        let recipients = fee_recipients(&[7_000, 2_000, 1_000]);
+        assert_eq!(split_fee(1_000, &recipients, 0), vec![700, 200, 100]);
+        
+        for fee_amount in [0, 1, 9, 999, 1_000_003, u64::MAX] {
+            let shares = split_fee(fee_amount, &recipients, 1);
+            assert_eq!(shares.iter().map(|s| *s as u128).sum::<u128>(), fee_amount as u128);
+        }
+    }
     
-    /// 시스템 프로그램
-    pub system_program: Program<'info, System>,
-}
-
-/// 임시 키 철회 명령어 계정 구조체
-#[derive(Accounts)]
-pub struct RevokeTempKey<'info> {
-    /// 권한 있는 사용자 (관리자)
-    pub authority: Signer<'info>,
+    fn fee_policy(min_fee_amount: u64, max_fee_amount: u64, fee_mode: FeeMode) -> FeePolicy {
+        FeePolicy {
+            fee_collector: Pubkey::new_unique(),
+            sol_fee_bps: 0,
+            token_fee_bps: 0,
+            min_fee_amount,
+            authority: Pubkey::new_unique(),
+            max_fee_amount,
+            fee_mode,
+            fee_recipients: Vec::new(),
+            remainder_index: 0,
+            sol_fee_tiers: Vec::new(),
+            token_fee_tiers: Vec::new(),
+        }
+    }
     
-    /// 트랜잭션 지불자 (관리자)
-    #[account(mut)]
-    pub payer: Signer<'info>,
+    #[test]
+    fn calculate_fee_applies_minimum_and_cap() {
+        let uncapped = fee_policy(10, 0, FeeMode::Deduct);
+        assert_eq!(calculate_fee(100_000, 30, &uncapped).unwrap(), 300);
+        assert_eq!(calculate_fee(1_000, 30, &uncapped).unwrap(), 10);
+        
+        let capped = fee_policy(10, 250, FeeMode::Deduct);
+        assert_eq!(calculate_fee(100_000, 30, &capped).unwrap(), 250);
+        assert_eq!(calculate_fee(50_000, 30, &capped).unwrap(), 150);
+        assert_eq!(calculate_fee(1_000, 30, &capped).unwrap(), 10);
+    }
     
-    /// 임시 키 매핑 데이터
-    #[account(
-        mut,
-        seeds = [
-            b"key_mapping",
-            key_mapping.temp_key.as_ref(),
-        ],
-        bump,
-    )]
-    pub key_mapping: Account<'info, KeyMapping>,
-}
-
-/// 백업 키 변경 명령어 계정 구조체
-#[derive(Accounts)]
-pub struct ChangeBackupKey<'info> {
-    /// 현재 백업 키 서명자 (현재 백업 키의 소유자)
-    pub current_backup_key_signer: Signer<'info>,
+    #[test]
+    fn deduct_mode_rejects_fees_that_consume_the_transfer() {
+        let policy = fee_policy(10, 0, FeeMode::Deduct);
+        assert!(matches!(calculate_fee(10, 30, &policy), Err(ErrorCode::FeeTooHigh)));
+        assert_eq!(calculate_fee(11, 30, &policy).unwrap(), 10);
+    }
     
-    /// 새로운 백업 키 계정
-    /// CHECK: 백업 키는 서명 검증이 필요 없음, 단순 식별자로 사용
-    pub new_backup_key: AccountInfo<'info>,
+    #[test]
+    fn sender_pays_mode_allows_fees_above_the_amount() {
+        // 송신자가 금액과 별도로 수수료를 지불하므로 최소 수수료가 금액보다 커도 됨
+        let policy = fee_policy(10, 0, FeeMode::SenderPays);
+        assert_eq!(calculate_fee(1, 30, &policy).unwrap(), 10);
+        assert_eq!(calculate_fee(0, 30, &policy).unwrap(), 10);
+    }
     
-    /// 임시 키 매핑 데이터
-    #[account(
-        mut,
-        seeds = [
-            b"key_mapping",
-            key_mapping.temp_key.as_ref(),
-        ],
-        bump,
-    )]
-    pub key_mapping: Account<'info, KeyMapping>,
-}
-
-/// SPL 토큰 전송 명령어 계정 구조체
-#[derive(Accounts)]
-pub struct TransferSplToken<'info> {
-    /// 수수료 지불자 (관리자 또는 서비스 제공자)
-    #[account(mut)]
-    pub fee_payer: Signer<'info>,
+    fn fee_tier(min_volume: u64, fee_bps: u16) -> FeeTier {
+        FeeTier { min_volume, fee_bps }
+    }
     
-    /// 임시 키 또는 백업 키 서명자
-    #[account(mut)]
-    pub temp_key: Signer<'info>,
+    #[test]
+    fn tier_selection_uses_the_highest_qualifying_tier() {
+        let tiers = [fee_tier(1_000, 20), fee_tier(100_000, 10)];
+        
+        assert_eq!(tiered_fee_bps(30, &[], u64::MAX), 30);
+        assert_eq!(tiered_fee_bps(30, &tiers, 999), 30);
+        assert_eq!(tiered_fee_bps(30, &tiers, 1_000), 20);
+        assert_eq!(tiered_fee_bps(30, &tiers, 99_999), 20);
+        assert_eq!(tiered_fee_bps(30, &tiers, 100_000), 10);
+    }
     
-    /// 토큰 Mint 주소
-    /// CHECK: 토큰 민트는 단순 식별자로만 사용됩니다
-    pub mint: AccountInfo<'info>,
+    #[test]
+    fn sol_fee_uses_the_sol_rate_tiers_and_minimum() {
+        use anchor_lang::solana_program::native_token::LAMPORTS_PER_SOL;
+        
+        let mut policy = fee_policy(5_000, 0, FeeMode::Deduct);
+        policy.sol_fee_bps = 50;
+        policy.token_fee_bps = 10;
+        policy.sol_fee_tiers = vec![fee_tier(100 * LAMPORTS_PER_SOL, 20)];
+        
+        // 토큰 수수료율이 아닌 SOL 수수료율 적용 (1 SOL의 0.5%)
+        let fee_bps = sol_fee_bps(&policy, 0);
+        assert_eq!(fee_bps, 50);
+        assert_eq!(calculate_fee(LAMPORTS_PER_SOL, fee_bps, &policy).unwrap(), 5_000_000);
+        // 소액 전송에는 최소 수수료(lamports) 적용
+        assert_eq!(calculate_fee(100_000, fee_bps, &policy).unwrap(), 5_000);
+        assert!(matches!(calculate_fee(5_000, fee_bps, &policy), Err(ErrorCode::FeeTooHigh)));
+        
+        // 30일 SOL 거래량이 구간에 도달하면 낮은 수수료율 적용
+        let fee_bps = sol_fee_bps(&policy, 100 * LAMPORTS_PER_SOL);
+        assert_eq!(fee_bps, 20);
+        assert_eq!(calculate_fee(LAMPORTS_PER_SOL, fee_bps, &policy).unwrap(), 2_000_000);
+    }
     
-    /// 송신자 토큰 계정
-    #[account(mut)]
-    pub from: Account<'info, TokenAccount>,
+    #[test]
+    fn sol_transfers_check_system_program_scope_and_native_mint_limits() {
+        let native_mint = token::spl_token::native_mint::ID;
+        
+        // 세션 권한 범위는 시스템 프로그램과 SOL 전송 함수 ID 기준
+        let mut key_mapping = key_mapping(Pubkey::new_unique(), "alice", 100);
+        key_mapping.scope.allowed_programs = vec![system_program::ID];
+        key_mapping.scope.allowed_functions = vec![FUNCTION_TRANSFER_SOL];
+        key_mapping.scope.spend_limit = 1_000;
+        assert!(
+            check_session_scope(&key_mapping, &system_program::ID, FUNCTION_TRANSFER_SOL, 1_000).is_ok()
+        );
+        // 송신자 부담 수수료는 지출 한도 검증 금액에 포함됨
+        assert!(matches!(
+            check_session_scope(&key_mapping, &system_program::ID, FUNCTION_TRANSFER_SOL, 1_001),
+            Err(ErrorCode::SessionSpendLimitExceeded)
+        ));
+        assert!(matches!(
+            check_session_scope(&key_mapping, &system_program::ID, FUNCTION_TRANSFER_SPL_TOKEN, 0),
+            Err(ErrorCode::FunctionNotInScope)
+        ));
+        
+        // 보안 정책은 (시스템 프로그램, SOL 전송) 호출과 네이티브 Mint 한도로 검증
+        let mut limits = policy_limits();
+        limits.allowed_calls = vec![AllowedCall {
+            program_id: system_program::ID,
+            function_id: FUNCTION_TRANSFER_SOL,
+        }];
+        let mut policy = security_policy(limits);
+        policy.mint_limits = vec![mint_limit(native_mint, 500, 1_000)];
+        
+        let apply_sol = |policy: &mut SecurityPolicy, function_id: u8, amount: u64| {
+            apply_security_policy(policy, &system_program::ID, function_id, Some(native_mint), amount, 0)
+        };
+        assert!(apply_sol(&mut policy, FUNCTION_TRANSFER_SOL, 500).is_ok());
+        assert!(matches!(
+            apply_sol(&mut policy, FUNCTION_TRANSFER_SOL, 501),
+            Err(ErrorCode::MintTxLimitExceeded)
+        ));
+        assert!(matches!(
+            apply_sol(&mut policy, FUNCTION_TRANSFER_SPL_TOKEN, 1),
+            Err(ErrorCode::CallNotAllowed)
+        ));
+    }
     
-    /// 수신자 토큰 계정
-    #[account(mut)]
-    pub to: Account<'info, TokenAccount>,
+    #[test]
+    fn token_fee_uses_default_tiers_without_a_token_fee_policy() {
+        let mut policy = fee_policy(0, 0, FeeMode::Deduct);
+        policy.token_fee_bps = 30;
+        policy.token_fee_tiers = vec![fee_tier(1_000, 20)];
+        
+        // 토큰별 정책이 없는 Mint는 기본 토큰 수수료율과 구간 적용
+        assert_eq!(token_fee_bps(&policy, None, 999), 30);
+        assert_eq!(token_fee_bps(&policy, None, 1_000), 20);
+        
+        // 토큰별 정책이 있으면 해당 정책의 수수료율과 구간 적용
+        let token_policy = TokenFeePolicy {
+            token_mint: Pubkey::new_unique(),
+            fee_bps: 15,
+            fee_tiers: vec![fee_tier(500, 5)],
+        };
+        assert_eq!(token_fee_bps(&policy, Some(&token_policy), 499), 15);
+        assert_eq!(token_fee_bps(&policy, Some(&token_policy), 1_000), 5);
+    }
     
-    /// 수수료 수금자 토큰 계정
-    #[account(mut)]
-    pub fee_collector: Account<'info, TokenAccount>,
+    #[test]
+    fn fee_tiers_must_be_ascending_and_bounded() {
+        assert!(validate_fee_tiers(&[fee_tier(1_000, 20), fee_tier(100_000, 10)]).is_ok());
+        assert!(matches!(
+            validate_fee_tiers(&[fee_tier(1_000, 20), fee_tier(1_000, 10)]),
+            Err(ErrorCode::InvalidFeeTiers)
+        ));
+        assert!(matches!(validate_fee_tiers(&[fee_tier(0, 2_001)]), Err(ErrorCode::FeeTooHigh)));
+        
+        let too_many: Vec<FeeTier> = (0..=MAX_FEE_TIERS as u64).map(|i| fee_tier(i, 10)).collect();
+        assert!(matches!(validate_fee_tiers(&too_many), Err(ErrorCode::InvalidFeeTiers)));
+    }
     
-    /// 임시 키 매핑 데이터
-    #[account(
-        seeds = [b"key_mapping", temp_key.key().as_ref()],
-        bump
-    )]
-    pub key_mapping: Account<'info, KeyMapping>,
+    #[test]
+    fn volume_tracker_keeps_a_rolling_thirty_day_total() {
+        let mint = Pubkey::new_unique();
+        let mut tracker = VolumeTracker {
+            user_id: String::new(),
+            mint: Pubkey::default(),
+            daily_volumes: [0; VOLUME_WINDOW_DAYS],
+            last_day: 0,
+        };
+        let day = |n: i64| n * SECONDS_PER_DAY;
+        
+        assert_eq!(refresh_volume_tracker(&mut tracker, "alice", mint, day(100)), 0);
+        assert_eq!(tracker.mint, mint);
+        record_volume(&mut tracker, 500, day(100));
+        
+        assert_eq!(refresh_volume_tracker(&mut tracker, "alice", mint, day(110)), 500);
+        record_volume(&mut tracker, 300, day(110));
+        
+        // 30일이 지난 날의 거래량은 집계에서 빠짐
+        assert_eq!(refresh_volume_tracker(&mut tracker, "alice", mint, day(130)), 300);
+        assert_eq!(refresh_volume_tracker(&mut tracker, "alice", mint, day(200)), 0);
+    }
     
-    /// 수수료 정책 (선택적)
-    #[account(
-        seeds = [b"fee_policy"],
-        bump,
-    )]
-    pub fee_policy: Option<Account<'info, FeePolicy>>,
+    #[test]
+    fn legacy_fee_policies_migrate_with_default_fields() {
+        let legacy = LegacyFeePolicy {
+            fee_collector: Pubkey::new_unique(),
+            sol_fee_bps: 30,
+            token_fee_bps: 50,
+            min_fee_amount: 5_000,
+            authority: Pubkey::new_unique(),
+        };
+        let mut data = FeePolicy::discriminator().to_vec();
+        data.extend(legacy.try_to_vec().unwrap());
+        assert_eq!(data.len(), LEGACY_FEE_POLICY_SPACE);
+        
+        let fee_policy = migrated_fee_policy(&data).unwrap();
+        assert_eq!(fee_policy.fee_collector, legacy.fee_collector);
+        assert_eq!((fee_policy.sol_fee_bps, fee_policy.token_fee_bps), (30, 50));
+        assert_eq!(fee_policy.min_fee_amount, 5_000);
+        assert_eq!(fee_policy.authority, legacy.authority);
+        assert_eq!(fee_policy.max_fee_amount, 0);
+        assert!(fee_policy.fee_mode == FeeMode::Deduct);
+        assert!(fee_policy.fee_recipients.is_empty() && fee_policy.sol_fee_tiers.is_empty());
+        
+        // 이미 마이그레이션된 계정은 다시 변환하지 않음
+        let mut current = Vec::new();
+        fee_policy.try_serialize(&mut current).unwrap();
+        current.resize(FEE_POLICY_SPACE, 0);
+        assert!(matches!(migrated_fee_policy(&current), Err(ErrorCode::NotLegacyAccount)));
+    }
     
-    /// 토큰별 수수료 정책 (선택적)
-    #[account(
-        seeds = [b"token_fee_policy", mint.key().as_ref()],
-        bump,
-    )]
-    pub token_fee_policy: Option<Account<'info, TokenFeePolicy>>,
+    #[test]
+    fn legacy_token_fee_policies_migrate_without_tiers() {
+        let legacy = LegacyTokenFeePolicy { token_mint: Pubkey::new_unique(), fee_bps: 25 };
+        let mut data = TokenFeePolicy::discriminator().to_vec();
+        data.extend(legacy.try_to_vec().unwrap());
+        assert_eq!(data.len(), LEGACY_TOKEN_FEE_POLICY_SPACE);
+        
+        let token_fee_policy = migrated_token_fee_policy(&data).unwrap();
+        assert_eq!(token_fee_policy.token_mint, legacy.token_mint);
+        assert_eq!(token_fee_policy.fee_bps, 25);
+        assert!(token_fee_policy.fee_tiers.is_empty());
+        
+        // 다른 계정 유형의 데이터는 거부
+        data[..8].copy_from_slice(&FeePolicy::discriminator());
+        assert!(matches!(migrated_token_fee_policy(&data), Err(ErrorCode::NotLegacyAccount)));
+    }
     
-    /// 보안 정책 (선택적)
-    #[account(
-        mut,
-        seeds = [b"security_policy", key_mapping.user_id.as_bytes()],
-        bump,
-        constraint = if security_policy.to_account_info().owner == &system_program::ID {
-            true
-        } else {
-            security_policy.user_id == key_mapping.user_id
-        }
-    )]
-    pub security_policy: Option<Account<'info, SecurityPolicy>>,
+    #[test]
+    fn fee_policy_space_fits_fully_populated_accounts() {
+        let mut fee_policy = fee_policy(0, 0, FeeMode::SenderPays);
+        fee_policy.fee_recipients = fee_recipients(&[1; MAX_FEE_RECIPIENTS]);
+        fee_policy.sol_fee_tiers = (0..MAX_FEE_TIERS as u64).map(|i| fee_tier(i, 10)).collect();
+        fee_policy.token_fee_tiers = fee_policy.sol_fee_tiers.clone();
+        assert_eq!(8 + fee_policy.try_to_vec().unwrap().len(), FEE_POLICY_SPACE);
+        
+        let token_fee_policy = TokenFeePolicy {
+            token_mint: Pubkey::new_unique(),
+            fee_bps: 0,
+            fee_tiers: fee_policy.sol_fee_tiers.clone(),
+        };
+        assert_eq!(8 + token_fee_policy.try_to_vec().unwrap().len(), TOKEN_FEE_POLICY_SPACE);
+    }
     
-    /// 토큰 프로그램
-    pub token_program: Program<'info, Token>,
-}
-
-/// 트랜잭션 릴레이 명령어 계정 구조체
-#[derive(Accounts)]
-pub struct RelayTransaction<'info> {
-    /// 수수료 지불자 (관리자 또는 서비스 제공자)
-    #[account(mut)]
-    pub fee_payer: Signer<'info>,
+    /// 오프체인 테스트용 Rent sysvar 제공 (Rent::get이 기본값을 반환하도록)
+    struct RentSyscallStubs;
     
-    /// 임시 키 또는 백업 키 서명자
-    #[account(mut)]
-    pub temp_key: Signer<'info>,
+    impl anchor_lang::solana_program::program_stubs::SyscallStubs for RentSyscallStubs {
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Rent) = Rent::default() };
+            anchor_lang::solana_program::entrypoint::SUCCESS
+        }
+    }
     
-    /// AA 릴레이 프로그램 ID
-    /// CHECK: 프로그램 ID는 유저 계정에 저장된 값과 일치하는지 확인하는 용도로만 사용
+    fn use_rent_sysvar() {
+        static INIT: std::sync::Once = std::sync::Once::new();
+        INIT.call_once(|| {
+            let stubs = Box::new(RentSyscallStubs);
+            anchor_lang::solana_program::program_stubs::set_syscall_stubs(stubs);
+        });
+    }
     
-    /// 임시 키 매핑 데이터
-    #[account(
-        mut,
-        seeds = [b"key_mapping", temp_key.key().as_ref()],
-        bump
-    )]
-    pub key_mapping: Account<'info, KeyMapping>,
+    const REIMBURSEMENT: u64 = 5_000;
     
-    /// 보안 정책 (선택적)
-    #[account(
-        mut,
-        seeds = [b"security_policy", key_mapping.user_id.as_bytes()],
-        bump,
-        constraint = if security_policy.to_account_info().owner == &system_program::ID {
-            true
-        } else {
-            security_policy.user_id == key_mapping.user_id
-        }
-    )]
-    pub security_policy: Option<Account<'info, SecurityPolicy>>,
+    /// 렌트 면제 최소 잔액에 `spare_calls`회 보전할 잔액을 더한 페이마스터
+    fn paymaster_account(
+        daily_budget: u64,
+        user_daily_budget: u64,
+        spare_calls: u64,
+    ) -> TestAccount {
+        let paymaster = Paymaster {
+            sponsor: Pubkey::new_unique(),
+            reimbursement_per_call: REIMBURSEMENT,
+            daily_budget,
+            user_daily_budget,
+            current_day: 0,
+            spent_today: 0,
+            total_reimbursed: 0,
+        };
+        let mut account = TestAccount::new(Pubkey::new_unique(), &paymaster);
+        let min_balance = Rent::default().minimum_balance(account.data.len());
+        account.lamports = min_balance + spare_calls * REIMBURSEMENT;
+        account
+    }
     
-    /// 시스템 프로그램 (수수료 처리용)
-    pub system_program: Program<'info, System>,
-}
-
-#[error_code]
-pub enum ErrorCode {
-    #[msg("권한이 없습니다")]
-    Unauthorized,
+    fn paymaster_usage_account(paymaster: Pubkey, user_id: &str) -> TestAccount {
+        let usage = PaymasterUsage {
+            paymaster,
+            user_id: user_id.to_string(),
+            current_day: 0,
+            spent_today: 0,
+        };
+        TestAccount::new(Pubkey::new_unique(), &usage)
+    }
     
-    #[msg("세션이 만료되었습니다")]
-    SessionExpired,
+    fn fee_payer_account() -> TestAccount {
+        TestAccount::raw(Pubkey::new_unique(), system_program::ID, Vec::new())
+    }
     
-    #[msg("세션이 철회되었습니다")]
-    SessionRevoked,
+    /// 페이마스터 보전 1회 실행
+    fn reimburse<'info>(
+        paymaster: &AccountInfo<'info>,
+        usage: &AccountInfo<'info>,
+        fee_payer: &AccountInfo<'info>,
+        now: i64,
+    ) -> Result<()> {
+        let mut paymaster: Account<Paymaster> = Account::try_from(paymaster).unwrap();
+        let mut usage: Account<PaymasterUsage> = Account::try_from(usage).unwrap();
+        let user_id = usage.user_id.clone();
+        reimburse_fee_payer(Some(&mut paymaster), Some(&mut usage), fee_payer, &user_id, now)?;
+        paymaster.exit(&crate::ID)?;
+        usage.exit(&crate::ID)
+    }
     
-    #[msg("이미 철회된 키입니다")]
-    AlreadyRevoked,
+    #[test]
+    fn paymaster_stops_at_the_user_daily_budget_and_resets_next_day() {
+        use_rent_sysvar();
+        let mut paymaster = paymaster_account(10 * REIMBURSEMENT, 2 * REIMBURSEMENT, 10);
+        let mut usage = paymaster_usage_account(paymaster.key, "alice");
+        let mut fee_payer = fee_payer_account();
+        let starting_balance = fee_payer.lamports;
+        let (paymaster, usage, fee_payer) = (paymaster.info(), usage.info(), fee_payer.info());
+        
+        assert!(reimburse(&paymaster, &usage, &fee_payer, 0).is_ok());
+        assert!(reimburse(&paymaster, &usage, &fee_payer, 0).is_ok());
+        let err = reimburse(&paymaster, &usage, &fee_payer, 0).unwrap_err();
+        assert_eq!(err, ErrorCode::PaymasterUserBudgetExceeded.into());
+        assert_eq!(fee_payer.lamports(), starting_balance + 2 * REIMBURSEMENT);
+        
+        // 다음 날에는 사용자별 한도가 다시 채워짐
+        assert!(reimburse(&paymaster, &usage, &fee_payer, SECONDS_PER_DAY).is_ok());
+        assert_eq!(fee_payer.lamports(), starting_balance + 3 * REIMBURSEMENT);
+    }
     
-    #[msg("유효하지 않은 키 서명자입니다")]
-    InvalidKeySigner,
+    #[test]
+    fn paymaster_daily_budget_is_shared_across_users() {
+        use_rent_sysvar();
+        let mut paymaster = paymaster_account(3 * REIMBURSEMENT, 2 * REIMBURSEMENT, 10);
+        let mut alice = paymaster_usage_account(paymaster.key, "alice");
+        let mut bob = paymaster_usage_account(paymaster.key, "bob");
+        let mut fee_payer = fee_payer_account();
+        let (paymaster, alice, bob, fee_payer) =
+            (paymaster.info(), alice.info(), bob.info(), fee_payer.info());
+        
+        assert!(reimburse(&paymaster, &alice, &fee_payer, 0).is_ok());
+        assert!(reimburse(&paymaster, &alice, &fee_payer, 0).is_ok());
+        assert!(reimburse(&paymaster, &bob, &fee_payer, 0).is_ok());
+        let err = reimburse(&paymaster, &bob, &fee_payer, 0).unwrap_err();
+        assert_eq!(err, ErrorCode::PaymasterDailyBudgetExceeded.into());
+    }
     
-    #[msg("권한이 없는 키 서명자입니다")]
-    UnauthorizedKeySigner,
+    #[test]
+    fn paymaster_keeps_its_rent_exempt_balance() {
+        use_rent_sysvar();
+        let mut paymaster = paymaster_account(10 * REIMBURSEMENT, 10 * REIMBURSEMENT, 1);
+        let mut usage = paymaster_usage_account(paymaster.key, "alice");
+        let mut fee_payer = fee_payer_account();
+        let (paymaster, usage, fee_payer) = (paymaster.info(), usage.info(), fee_payer.info());
+        
+        assert!(reimburse(&paymaster, &usage, &fee_payer, 0).is_ok());
+        let err = reimburse(&paymaster, &usage, &fee_payer, 0).unwrap_err();
+        assert_eq!(err, ErrorCode::PaymasterInsufficientFunds.into());
+        assert_eq!(paymaster.lamports(), Rent::default().minimum_balance(paymaster.data_len()));
+    }
     
-    #[msg("철회된 키입니다")]
-    RevokedKey,
+    #[test]
+    fn paymaster_only_sponsors_enrolled_users() {
+        use_rent_sysvar();
+        let mut paymaster = paymaster_account(10 * REIMBURSEMENT, 10 * REIMBURSEMENT, 10);
+        let mut other_usage = paymaster_usage_account(Pubkey::new_unique(), "alice");
+        let mut fee_payer = fee_payer_account();
+        let (paymaster, other_usage, fee_payer) =
+            (paymaster.info(), other_usage.info(), fee_payer.info());
+        
+        // 다른 페이마스터에 등록된 사용량 계정
+        let err = reimburse(&paymaster, &other_usage, &fee_payer, 0).unwrap_err();
+        assert_eq!(err, ErrorCode::PaymasterUsageMissing.into());
+        
+        // 등록 계정 없이 페이마스터만 전달
+        let mut paymaster: Account<Paymaster> = Account::try_from(&paymaster).unwrap();
+        let err =
+            reimburse_fee_payer(Some(&mut paymaster), None, &fee_payer, "alice", 0).unwrap_err();
+        assert_eq!(err, ErrorCode::PaymasterUsageMissing.into());
+        
+        // 페이마스터를 쓰지 않는 호출은 영향 없음
+        assert!(reimburse_fee_payer(None, None, &fee_payer, "alice", 0).is_ok());
+    }
     
-    #[msg("만료된 키입니다")]
-    ExpiredKey,
+    #[test]
+    fn session_use_counts_only_the_main_key() {
+        let mint = Pubkey::new_unique();
+        let mut key_mapping = key_mapping(Pubkey::new_unique(), "alice", 100);
+        
+        let function_id = FUNCTION_TRANSFER_SPL_TOKEN;
+        record_session_use(&mut key_mapping, true, function_id, Some(mint), 100, 10);
+        // 백업 키 등 다른 서명 키 사용은 권한 범위 집계에서 제외되지만 사용 내역은 기록됨
+        record_session_use(&mut key_mapping, false, FUNCTION_TRANSFER_SOL, Some(mint), 50, 20);
+        
+        assert_eq!(key_mapping.use_count, 1);
+        assert_eq!(key_mapping.spent_amount, 100);
+        assert_eq!(key_mapping.usage.last_used_at, 20);
+        assert_eq!(key_mapping.usage.last_function_id, FUNCTION_TRANSFER_SOL);
+        assert_eq!(key_mapping.usage.mint_totals[0].total_amount, 150);
+    }
     
-    #[msg("수수료가 너무 높습니다")]
-    FeeTooHigh,
+    #[test]
+    fn session_mint_totals_evict_the_least_recently_used_mint() {
+        let mints: Vec<Pubkey> = (0..=MAX_SESSION_MINTS).map(|_| Pubkey::new_unique()).collect();
+        let mut key_mapping = key_mapping(Pubkey::new_unique(), "alice", 100);
+        let mut transfer = |mint: Pubkey| {
+            record_session_use(&mut key_mapping, true, FUNCTION_TRANSFER_SPL_TOKEN, Some(mint), 1, 0)
+        };
+        
+        for mint in &mints[..MAX_SESSION_MINTS] {
+            transfer(*mint);
+        }
+        // 가장 먼저 기록된 토큰을 다시 사용하면 최근 사용으로 이동
+        transfer(mints[0]);
+        // 가득 찬 상태에서 새 토큰이 들어오면 가장 오래 사용되지 않은 토큰(mints[1])이 제거됨
+        let new_mint = mints[MAX_SESSION_MINTS];
+        transfer(new_mint);
+        
+        let totals = &key_mapping.usage.mint_totals;
+        assert_eq!(totals.len(), MAX_SESSION_MINTS);
+        assert!(!totals.iter().any(|t| t.mint == mints[1]));
+        assert_eq!(totals.iter().find(|t| t.mint == mints[0]).unwrap().total_amount, 2);
+        assert_eq!(totals.last().unwrap().mint, new_mint);
+        assert_eq!(key_mapping.use_count, MAX_SESSION_MINTS as u32 + 2);
+    }
     
-    #[msg("일일 트랜잭션 한도를 초과했습니다")]
-    DailyTxLimitExceeded,
+    #[test]
+    fn zero_amount_calls_do_not_record_mint_totals() {
+        let mut key_mapping = key_mapping(Pubkey::new_unique(), "alice", 100);
+        let mint = Pubkey::new_unique();
+        record_session_use(&mut key_mapping, true, FUNCTION_CREATE_SWAP, Some(mint), 0, 5);
+        record_session_use(&mut key_mapping, true, FUNCTION_REGISTER_TOKEN, None, 0, 6);
+        
+        assert_eq!(key_mapping.use_count, 2);
+        assert!(key_mapping.usage.mint_totals.is_empty());
+    }
     
-    #[msg("트랜잭션 금액 한도를 초과했습니다")]
-    TxAmountLimitExceeded,
+    #[test]
+    fn mints_without_their_own_limit_keep_the_global_caps() {
+        let usdc = Pubkey::new_unique();
+        let bonk = Pubkey::new_unique();
+        let mut limits = policy_limits();
+        limits.max_amount_per_tx = 100;
+        limits.max_amount_per_window = 150;
+        let mut policy = security_policy(limits);
+        policy.mint_limits = vec![mint_limit(usdc, u64::MAX, u64::MAX)];
+        
+        // 다른 토큰에 한도를 추가해도 한도가 없는 토큰은 전역 한도를 그대로 적용받음
+        assert!(matches!(
+            apply(&mut policy, Some(bonk), 101, 0),
+            Err(ErrorCode::TxAmountLimitExceeded)
+        ));
+        assert!(apply(&mut policy, Some(bonk), 100, 0).is_ok());
+        assert!(matches!(
+            apply(&mut policy, Some(bonk), 51, 0),
+            Err(ErrorCode::WindowAmountLimitExceeded)
+        ));
+        // 한도가 설정된 토큰은 전역 한도와 무관함
+        assert!(apply(&mut policy, Some(usdc), 1_000_000, 0).is_ok());
+    }
     
-    #[msg("일일 금액 한도를 초과했습니다")]
-    DailyAmountLimitExceeded,
+    #[test]
+    fn mint_limits_above_the_global_caps_wait_for_the_timelock() {
+        let usdc = Pubkey::new_unique();
+        let bonk = Pubkey::new_unique();
+        let mut limits = policy_limits();
+        limits.max_amount_per_tx = 100;
+        limits.max_amount_per_window = 1_000;
+        limits.loosen_delay = DEFAULT_LOOSEN_DELAY;
+        let mut policy = security_policy(limits);
+        
+        // 첫 항목은 한도가 전역 한도보다 작아도 완화로 봄
+        assert!(is_mint_limit_loosened(&policy, usdc, 10, 10));
+        let loosening = is_mint_limit_loosened(&policy, usdc, u64::MAX, u64::MAX);
+        assert!(loosening);
+        let change = PolicyChange::SetMintLimit {
+            mint: usdc,
+            max_amount_per_tx: u64::MAX,
+            max_amount_per_window: u64::MAX,
+        };
+        assert!(stage_policy_change(&mut policy, change, loosening, 0).is_ok());
+        assert!(policy.mint_limits.is_empty());
+        assert!(policy.pending_change.is_some());
+        // 대기 중에는 전역 한도가 계속 적용됨
+        assert!(matches!(
+            apply(&mut policy, Some(usdc), 101, 0),
+            Err(ErrorCode::TxAmountLimitExceeded)
+        ));
+        
+        policy.pending_change = None;
+        policy.mint_limits = vec![mint_limit(usdc, 50, 500)];
+        // 전역 한도 이하의 신규 항목과 기존 항목의 하향은 즉시 적용
+        assert!(!is_mint_limit_loosened(&policy, bonk, 100, 1_000));
+        assert!(!is_mint_limit_loosened(&policy, usdc, 40, 500));
+        // 전역 한도를 넘는 신규 항목과 기존 항목의 상향은 완화
+        assert!(is_mint_limit_loosened(&policy, bonk, 101, 1_000));
+        assert!(is_mint_limit_loosened(&policy, bonk, 100, 1_001));
+        assert!(is_mint_limit_loosened(&policy, usdc, 50, 501));
+        
+        // 기본 거부 토큰의 신규 허용은 한도와 무관하게 완화
+        policy.limits.default_mint_allowed = false;
+        assert!(is_mint_limit_loosened(&policy, bonk, 1, 1));
+    }
     
-    #[msg("허용되지 않은 함수입니다")]
-    FunctionNotAllowed,
+    #[test]
+    fn relayed_calls_target_user_account_execute_transaction() {
+        let signer = Pubkey::new_unique();
+        let user_account_pda = Pubkey::new_unique();
+        let ix = user_account_instruction(&signer, &user_account_pda, FUNCTION_TRANSFER, &[7, 8, 9]);
+        
+        assert_eq!(ix.program_id, user_account::UserAccount::id());
+        // Anchor 식별자 + function_id(u8) + params(Vec<u8>, 길이 u32 접두)
+        assert_eq!(ix.data[..8], hash(b"global:execute_transaction").to_bytes()[..8]);
+        assert_eq!(ix.data[8..], [FUNCTION_TRANSFER, 3, 0, 0, 0, 7, 8, 9]);
+        
+        // 계정 순서는 사용자 계정 프로그램의 ExecuteTransaction과 동일해야 함
+        assert_eq!(ix.accounts.len(), 3);
+        assert!(ix.accounts[0].pubkey == signer && ix.accounts[0].is_signer);
+        assert!(ix.accounts[1].pubkey == crate::ID && !ix.accounts[1].is_writable);
+        assert!(ix.accounts[2].pubkey == user_account_pda && ix.accounts[2].is_writable);
+    }
 }
@@ -1,6 +1,8 @@
 use anchor_lang::prelude::*;
+use crate::user_account::ErrorCode;
 declare_id!("G6p3b6vh9YcXrwKrGzkmHxh9ynDVvCbx2ShRGamofrL4");
 
+#[program]
 pub mod user_account {
     use super::*;
 
@@ -36,6 +38,9 @@ pub mod user_account {
         
         #[msg("유효하지 않은 수수료 설정입니다")]
         InvalidFeeSettings,
+        
+        #[msg("AA 릴레이에 의해 동결된 계정입니다")]
+        AccountFrozen,
     }
 
     /// 사용자 계정 초기화 함수
@@ -56,6 +61,7 @@ pub mod user_account {
         user_account.created_at = Clock::get()?.unix_timestamp;
         user_account.transaction_count = 0;
         user_account.paused = false;
+        user_account.relay_frozen = false;
         user_account.sol_balance = 0;
         user_account.tokens = Vec::new();
         
@@ -82,7 +88,8 @@ pub mod user_account {
     ) -> Result<()> {
         let user_account = &mut ctx.accounts.user_account;
         
-        // 계정이 일시중지 상태가 아닌지 확인
+        // 계정이 일시중지 또는 동결 상태가 아닌지 확인
+        require!(!user_account.relay_frozen, ErrorCode::AccountFrozen);
         require!(!user_account.paused, ErrorCode::AccountPaused);
         
         // AA 릴레이 프로그램으로부터 호출되었는지 확인
@@ -128,6 +135,35 @@ pub mod user_account {
         Ok(())
     }
 
+    /// AA 릴레이 긴급 동결/해제 함수 (AA 릴레이 프로그램의 relay_authority PDA 서명 필요)
+    /// 
+    /// 동결 여부(`relay_frozen`)만 변경하며 생성자의 일시중지 상태(`paused`)는 건드리지 않음.
+    /// 동결 해제는 AA 릴레이에서만 가능
+    /// 
+    /// * `frozen` - 동결 여부
+    pub fn set_relay_freeze(ctx: Context<RelayOperation>, frozen: bool) -> Result<()> {
+        let user_account = &mut ctx.accounts.user_account;
+        
+        // 등록된 AA 릴레이 프로그램의 권한 PDA인지 확인
+        let (relay_authority, _) = Pubkey::find_program_address(
+            &[b"relay_authority"],
+            &user_account.aa_relay_program,
+        );
+        require!(
+            ctx.accounts.relay_authority.key() == relay_authority,
+            ErrorCode::Unauthorized
+        );
+        
+        user_account.relay_frozen = frozen;
+        
+        if frozen {
+            msg!("사용자 계정이 AA 릴레이에 의해 동결되었습니다. 사용자 ID: {}", user_account.user_id);
+        } else {
+            msg!("사용자 계정의 동결이 해제되었습니다. 사용자 ID: {}", user_account.user_id);
+        }
+        Ok(())
+    }
+
     /// 토큰 잔액 추가 (테스트용)
     pub fn add_token_balance(
         ctx: Context<AdminOperation>,
@@ -195,195 +231,195 @@ pub mod user_account {
             sol_fee_basis_points, token_fee_basis_points);
         Ok(())
     }
+}
+
+// transfer_token 로직을 옮긴 새 함수
+fn handle_transfer_token(
+    user_account: &mut Account<UserAccountData>,
+    params: &[u8],
+) -> Result<()> {
+    // 파라미터 길이 검증 (최소 40바이트: 8바이트 amount + 32바이트 recipient)
+    if params.len() < 40 {
+        return Err(ErrorCode::InvalidParameters.into());
+    }
     
-    // transfer_token 로직을 옮긴 새 함수
-    fn handle_transfer_token(
-        user_account: &mut Account<UserAccountData>,
-        params: &[u8],
-    ) -> Result<()> {
-        // 파라미터 길이 검증 (최소 40바이트: 8바이트 amount + 32바이트 recipient)
-        if params.len() < 40 {
-            return Err(ErrorCode::InvalidParameters.into());
-        }
+    // 데이터 파싱 최적화 (slice 직접 변환)
+    let amount = u64::from_le_bytes(params[0..8].try_into().unwrap());
+    let recipient_array: [u8; 32] = params[8..40].try_into().unwrap();
+    let _recipient = Pubkey::from(recipient_array);
+    let token_mint_bytes = params.len() >= 72;
+    
+    // 토큰 전송 처리 (SOL 또는 토큰)
+    if token_mint_bytes {
+        // 토큰 전송
+        let token_mint_array: [u8; 32] = params[40..72].try_into().unwrap();
+        let token_mint = Pubkey::from(token_mint_array);
         
-        // 데이터 파싱 최적화 (slice 직접 변환)
-        let amount = u64::from_le_bytes(params[0..8].try_into().unwrap());
-        let recipient_array: [u8; 32] = params[8..40].try_into().unwrap();
-        let _recipient = Pubkey::from(recipient_array);
-        let token_mint_bytes = params.len() >= 72;
-        
-        // 토큰 전송 처리 (SOL 또는 토큰)
-        if token_mint_bytes {
-            // 토큰 전송
-            let token_mint_array: [u8; 32] = params[40..72].try_into().unwrap();
-            let token_mint = Pubkey::from(token_mint_array);
-            
-            // 토큰 잔액 조회 (효율적 탐색)
-            let token_idx = match user_account.tokens.iter().position(|t| t.mint == token_mint) {
-                Some(idx) => idx,
-                None => return Err(ErrorCode::TokenNotFound.into()),
-            };
-            
-            // 잔액 검증
-            let token = &user_account.tokens[token_idx];
-            if token.balance < amount {
-                return Err(ErrorCode::InsufficientFunds.into());
-            }
-            
-            // 수수료 계산 (안전한 수학 연산)
-            let fee_basis_points = user_account.fee_settings.token_fee_basis_points as u64;
-            
-            // 비트 시프트로 곱셈/나눗셈 최적화
-            let fee_amount = if fee_basis_points > 0 {
-                let fee = amount.saturating_mul(fee_basis_points) / 10_000;
-                fee.max(user_account.fee_settings.min_fee_amount)
-            } else {
-                user_account.fee_settings.min_fee_amount
-            };
-            
-            // 잔액 업데이트 (불변성 고려한 단일 업데이트)
-            user_account.tokens[token_idx].balance = user_account.tokens[token_idx].balance.saturating_sub(amount);
-            
-            // 수수료 처리 (수수료가 0보다 큰 경우만)
-            if fee_amount > 0 {
-                let _fee_collector = user_account.fee_settings.fee_collector;
-                
-                // name 미리 복제
-                let token_name = user_account.tokens[token_idx].name.clone();
-                
-                // 수수료 수금자 토큰 찾기 (인덱스 재활용)
-                let collector_idx = user_account.tokens.iter().position(|t| t.mint == token_mint && t.balance > 0);
-                
-                match collector_idx {
-                    Some(idx) => {
-                        // 기존 토큰 잔액에 수수료 추가
-                        user_account.tokens[idx].balance = user_account.tokens[idx].balance.saturating_add(fee_amount);
-                    },
-                    None => {
-                        // 새 토큰 잔액 생성 (name 복사 최소화)
-                        user_account.tokens.push(TokenBalance {
-                            mint: token_mint,
-                            balance: fee_amount,
-                            name: token_name,
-                        });
-                    }
-                }
-            }
-            
-            // 로그 간소화
-            msg!("토큰 전송: {} 단위, 수수료: {}", amount.saturating_sub(fee_amount), fee_amount);
-        } else {
-            // SOL 전송
-            require!(user_account.sol_balance >= amount, ErrorCode::InsufficientFunds);
-            
-            // 수수료 계산 (basis points: 1/100 of 1%)
-            let fee_basis_points = user_account.fee_settings.sol_fee_basis_points as u64;
-            let fee_amount = amount.saturating_mul(fee_basis_points) / 10000;
-            
-            // 실제 금액에서 수수료 제외
-            let transfer_amount = amount.saturating_sub(fee_amount);
-            
-            // SOL 잔액 업데이트 (단일 연산)
-            user_account.sol_balance = user_account.sol_balance.saturating_sub(amount);
-            
-            // 로그 간소화
-            msg!("SOL 전송: {} lamports, 수수료: {}", transfer_amount, fee_amount);
-        }
+        // 토큰 잔액 조회 (효율적 탐색)
+        let token_idx = match user_account.tokens.iter().position(|t| t.mint == token_mint) {
+            Some(idx) => idx,
+            None => return Err(ErrorCode::TokenNotFound.into()),
+        };
         
-        Ok(())
-    }
-    
-    fn handle_register_token(
-        user_account: &mut Account<UserAccountData>,
-        params: &[u8],
-    ) -> Result<()> {
-        // 파라미터 파싱 (token_mint: Pubkey, name: String)
-        if params.len() < 32 {
-            return Err(ErrorCode::InvalidParameters.into());
+        // 잔액 검증
+        let token = &user_account.tokens[token_idx];
+        if token.balance < amount {
+            return Err(ErrorCode::InsufficientFunds.into());
         }
         
-        // 토큰 민트 주소
-        let token_mint_array: [u8; 32] = params[0..32].try_into().unwrap();
-        let token_mint = Pubkey::from(token_mint_array);
+        // 수수료 계산 (안전한 수학 연산)
+        let fee_basis_points = user_account.fee_settings.token_fee_basis_points as u64;
         
-        // 토큰 이름 파싱
-        let name_bytes = if params.len() > 32 {
-            &params[32..]
+        // 비트 시프트로 곱셈/나눗셈 최적화
+        let fee_amount = if fee_basis_points > 0 {
+            let fee = amount.saturating_mul(fee_basis_points) / 10_000;
+            fee.max(user_account.fee_settings.min_fee_amount)
         } else {
-            b""
+            user_account.fee_settings.min_fee_amount
         };
         
-        let token_name = match std::str::from_utf8(name_bytes) {
-            Ok(s) => s.to_string(),
-            Err(_) => return Err(ErrorCode::InvalidParameters.into()),
-        };
+        // 잔액 업데이트 (불변성 고려한 단일 업데이트)
+        user_account.tokens[token_idx].balance = user_account.tokens[token_idx].balance.saturating_sub(amount);
         
-        // token_name 복제
-        let token_name_clone = token_name.clone();
-        
-        // 토큰 등록
-        // 이미 등록된 토큰인지 확인
-        if user_account.tokens.iter().any(|t| t.mint == token_mint) {
-            return Err(ErrorCode::TokenAlreadyRegistered.into());
+        // 수수료 처리 (수수료가 0보다 큰 경우만)
+        if fee_amount > 0 {
+            let _fee_collector = user_account.fee_settings.fee_collector;
+            
+            // name 미리 복제
+            let token_name = user_account.tokens[token_idx].name.clone();
+            
+            // 수수료 수금자 토큰 찾기 (인덱스 재활용)
+            let collector_idx = user_account.tokens.iter().position(|t| t.mint == token_mint && t.balance > 0);
+            
+            match collector_idx {
+                Some(idx) => {
+                    // 기존 토큰 잔액에 수수료 추가
+                    user_account.tokens[idx].balance = user_account.tokens[idx].balance.saturating_add(fee_amount);
+                },
+                None => {
+                    // 새 토큰 잔액 생성 (name 복사 최소화)
+                    user_account.tokens.push(TokenBalance {
+                        mint: token_mint,
+                        balance: fee_amount,
+                        name: token_name,
+                    });
+                }
+            }
         }
         
-        // 토큰 잔액 생성
-        user_account.tokens.push(TokenBalance {
-            mint: token_mint,
-            balance: 0,
-            name: token_name,
-        });
+        // 로그 간소화
+        msg!("토큰 전송: {} 단위, 수수료: {}", amount.saturating_sub(fee_amount), fee_amount);
+    } else {
+        // SOL 전송
+        require!(user_account.sol_balance >= amount, ErrorCode::InsufficientFunds);
         
-        // 복제된 값 사용
-        msg!("토큰이 등록되었습니다: {}, 이름: {}", token_mint, token_name_clone);
-        Ok(())
-    }
-    
-    fn handle_create_swap(
-        user_account: &mut Account<UserAccountData>,
-        params: &[u8],
-    ) -> Result<()> {
-        // 파라미터 파싱 (token_a: Pubkey, token_b: Pubkey, amount_a: u64, amount_b: u64)
-        if params.len() < 80 {
-            return Err(ErrorCode::InvalidParameters.into());
-        }
+        // 수수료 계산 (basis points: 1/100 of 1%)
+        let fee_basis_points = user_account.fee_settings.sol_fee_basis_points as u64;
+        let fee_amount = amount.saturating_mul(fee_basis_points) / 10000;
         
-        // 토큰 A 정보
-        let token_a_array: [u8; 32] = params[0..32].try_into().unwrap();
-        let token_a = Pubkey::from(token_a_array);
+        // 실제 금액에서 수수료 제외
+        let transfer_amount = amount.saturating_sub(fee_amount);
         
-        // 토큰 B 정보
-        let token_b_array: [u8; 32] = params[32..64].try_into().unwrap();
-        let token_b = Pubkey::from(token_b_array);
+        // SOL 잔액 업데이트 (단일 연산)
+        user_account.sol_balance = user_account.sol_balance.saturating_sub(amount);
         
-        // 교환 수량
-        let amount_a = u64::from_le_bytes(params[64..72].try_into().unwrap());
-        let amount_b = u64::from_le_bytes(params[72..80].try_into().unwrap());
-        
-        // 토큰 잔액 검증
-        let token_a_idx = user_account.tokens.iter()
-            .position(|t| t.mint == token_a)
-            .ok_or(ErrorCode::TokenNotFound)?;
-            
-        let token_b_idx = user_account.tokens.iter()
-            .position(|t| t.mint == token_b)
-            .ok_or(ErrorCode::TokenNotFound)?;
-            
-        // 토큰 A 잔액 체크
-        let token_a_balance = user_account.tokens[token_a_idx].balance;
-        require!(token_a_balance >= amount_a, ErrorCode::InsufficientFunds);
-        
-        // 스왑 실행 (실제로는 DEX와 연동할 수 있음)
-        // 이 예제에서는 단순히 잔액만 업데이트
-        user_account.tokens[token_a_idx].balance = token_a_balance.saturating_sub(amount_a);
-        user_account.tokens[token_b_idx].balance = user_account.tokens[token_b_idx].balance.saturating_add(amount_b);
-        
-        msg!("스왑 실행: {} {} => {} {}", 
-            amount_a, user_account.tokens[token_a_idx].name,
-            amount_b, user_account.tokens[token_b_idx].name);
-            
-        Ok(())
+        // 로그 간소화
+        msg!("SOL 전송: {} lamports, 수수료: {}", transfer_amount, fee_amount);
     }
+    
+    Ok(())
+}
+
+fn handle_register_token(
+    user_account: &mut Account<UserAccountData>,
+    params: &[u8],
+) -> Result<()> {
+    // 파라미터 파싱 (token_mint: Pubkey, name: String)
+    if params.len() < 32 {
+        return Err(ErrorCode::InvalidParameters.into());
+    }
+    
+    // 토큰 민트 주소
+    let token_mint_array: [u8; 32] = params[0..32].try_into().unwrap();
+    let token_mint = Pubkey::from(token_mint_array);
+    
+    // 토큰 이름 파싱
+    let name_bytes = if params.len() > 32 {
+        &params[32..]
+    } else {
+        b""
+    };
+    
+    let token_name = match std::str::from_utf8(name_bytes) {
+        Ok(s) => s.to_string(),
+        Err(_) => return Err(ErrorCode::InvalidParameters.into()),
+    };
+    
+    // token_name 복제
+    let token_name_clone = token_name.clone();
+    
+    // 토큰 등록
+    // 이미 등록된 토큰인지 확인
+    if user_account.tokens.iter().any(|t| t.mint == token_mint) {
+        return Err(ErrorCode::TokenAlreadyRegistered.into());
+    }
+    
+    // 토큰 잔액 생성
+    user_account.tokens.push(TokenBalance {
+        mint: token_mint,
+        balance: 0,
+        name: token_name,
+    });
+    
+    // 복제된 값 사용
+    msg!("토큰이 등록되었습니다: {}, 이름: {}", token_mint, token_name_clone);
+    Ok(())
+}
+
+fn handle_create_swap(
+    user_account: &mut Account<UserAccountData>,
+    params: &[u8],
+) -> Result<()> {
+    // 파라미터 파싱 (token_a: Pubkey, token_b: Pubkey, amount_a: u64, amount_b: u64)
+    if params.len() < 80 {
+        return Err(ErrorCode::InvalidParameters.into());
+    }
+    
+    // 토큰 A 정보
+    let token_a_array: [u8; 32] = params[0..32].try_into().unwrap();
+    let token_a = Pubkey::from(token_a_array);
+    
+    // 토큰 B 정보
+    let token_b_array: [u8; 32] = params[32..64].try_into().unwrap();
+    let token_b = Pubkey::from(token_b_array);
+    
+    // 교환 수량
+    let amount_a = u64::from_le_bytes(params[64..72].try_into().unwrap());
+    let amount_b = u64::from_le_bytes(params[72..80].try_into().unwrap());
+    
+    // 토큰 잔액 검증
+    let token_a_idx = user_account.tokens.iter()
+        .position(|t| t.mint == token_a)
+        .ok_or(ErrorCode::TokenNotFound)?;
+        
+    let token_b_idx = user_account.tokens.iter()
+        .position(|t| t.mint == token_b)
+        .ok_or(ErrorCode::TokenNotFound)?;
+        
+    // 토큰 A 잔액 체크
+    let token_a_balance = user_account.tokens[token_a_idx].balance;
+    require!(token_a_balance >= amount_a, ErrorCode::InsufficientFunds);
+    
+    // 스왑 실행 (실제로는 DEX와 연동할 수 있음)
+    // 이 예제에서는 단순히 잔액만 업데이트
+    user_account.tokens[token_a_idx].balance = token_a_balance.saturating_sub(amount_a);
+    user_account.tokens[token_b_idx].balance = user_account.tokens[token_b_idx].balance.saturating_add(amount_b);
+    
+    msg!("스왑 실행: {} {} => {} {}", 
+        amount_a, user_account.tokens[token_a_idx].name,
+        amount_b, user_account.tokens[token_b_idx].name);
+        
+    Ok(())
 }
 
 /// 수수료 설정 구조체
@@ -420,6 +456,8 @@ pub struct UserAccountData {
     pub tokens: Vec<TokenBalance>,
     /// 수수료 설정
     pub fee_settings: FeeSettings,
+    /// AA 릴레이 긴급 동결 여부 (생성자가 해제할 수 없음)
+    pub relay_frozen: bool,
 }
 
 /// 토큰 잔액 구조체
@@ -467,7 +505,9 @@ pub struct Initialize<'info> {
                32 + // fee_collector: Pubkey
                2 +  // sol_fee_basis_points: u16
                2 +  // token_fee_basis_points: u16
-               8    // min_fee_amount: u64
+               8 +  // min_fee_amount: u64
+               
+               1    // relay_frozen: bool
     )]
     pub user_account: Account<'info, UserAccountData>,
     
@@ -490,6 +530,17 @@ pub struct ExecuteTransaction<'info> {
     pub user_account: Account<'info, UserAccountData>,
 }
 
+/// AA 릴레이 긴급 동결 명령어 계정 구조체
+#[derive(Accounts)]
+pub struct RelayOperation<'info> {
+    /// AA 릴레이 프로그램의 권한 PDA (CPI 서명자)
+    pub relay_authority: Signer<'info>,
+    
+    /// 사용자 계정
+    #[account(mut)]
+    pub user_account: Account<'info, UserAccountData>,
+}
+
 /// 관리자 전용 명령어 계정 구조체
 #[derive(Accounts)]
 pub struct AdminOperation<'info> {
@@ -501,3 +552,106 @@ pub struct AdminOperation<'info> {
     #[account(mut)]
     pub user_account: Account<'info, UserAccountData>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::hash::hash;
+    use anchor_lang::solana_program::program_error::ProgramError;
+    
+    // AA 릴레이 프로그램 ID (aa_relay declare_id와 동일)
+    const AA_RELAY_PROGRAM: &str = "Dv9qDFdwsHpzcNZ7KxJgChV7yzAnMjTFDYp5JGCkzKvL";
+    
+    fn user_account_data(paused: bool) -> Vec<u8> {
+        let account = UserAccountData {
+            creator: Pubkey::new_unique(),
+            user_id: "alice".to_string(),
+            aa_relay_program: AA_RELAY_PROGRAM.parse().unwrap(),
+            created_at: 0,
+            transaction_count: 0,
+            paused,
+            sol_balance: 0,
+            tokens: Vec::new(),
+            fee_settings: FeeSettings {
+                fee_collector: Pubkey::new_unique(),
+                sol_fee_basis_points: 0,
+                token_fee_basis_points: 0,
+                min_fee_amount: 0,
+            },
+            relay_frozen: false,
+        };
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        data
+    }
+    
+    /// AA 릴레이가 CPI로 보내는 것과 같은 형식의 명령어 데이터
+    fn relay_freeze_data(frozen: bool) -> Vec<u8> {
+        let mut data = hash(b"global:set_relay_freeze").to_bytes()[..8].to_vec();
+        data.push(frozen as u8);
+        data
+    }
+    
+    /// 동결 명령어 실행 후 사용자 계정 상태 반환
+    fn run_relay_freeze(
+        relay_authority: Pubkey,
+        paused: bool,
+        frozen: bool,
+    ) -> std::result::Result<UserAccountData, ProgramError> {
+        let user_account = Pubkey::new_unique();
+        let mut authority_lamports = 0;
+        let mut user_lamports = 1_000_000_000;
+        let mut authority_data: Vec<u8> = Vec::new();
+        let mut data = user_account_data(paused);
+        let accounts = [
+            AccountInfo::new(
+                &relay_authority,
+                true,
+                false,
+                &mut authority_lamports,
+                &mut authority_data,
+                &anchor_lang::system_program::ID,
+                false,
+                0,
+            ),
+            AccountInfo::new(
+                &user_account,
+                false,
+                true,
+                &mut user_lamports,
+                &mut data,
+                &crate::ID,
+                false,
+                0,
+            ),
+        ];
+        
+        entry(&crate::ID, &accounts, &relay_freeze_data(frozen))?;
+        let data = accounts[1].try_borrow_data()?;
+        Ok(UserAccountData::try_deserialize(&mut &data[..]).unwrap())
+    }
+    
+    fn relay_authority() -> Pubkey {
+        let aa_relay_program: Pubkey = AA_RELAY_PROGRAM.parse().unwrap();
+        Pubkey::find_program_address(&[b"relay_authority"], &aa_relay_program).0
+    }
+    
+    #[test]
+    fn relay_freeze_sets_only_relay_frozen() {
+        let frozen = run_relay_freeze(relay_authority(), false, true).unwrap();
+        assert!(frozen.relay_frozen);
+        assert!(!frozen.paused);
+        
+        // 생성자의 일시중지 상태는 동결 해제 후에도 유지됨
+        let unfrozen = run_relay_freeze(relay_authority(), true, false).unwrap();
+        assert!(!unfrozen.relay_frozen);
+        assert!(unfrozen.paused);
+    }
+    
+    #[test]
+    fn relay_freeze_requires_the_relay_authority_pda() {
+        let expected: ProgramError = anchor_lang::error::Error::from(ErrorCode::Unauthorized).into();
+        let result = run_relay_freeze(Pubkey::new_unique(), false, true);
+        assert_eq!(result.map(|_| ()).unwrap_err(), expected);
+    }
+}